@@ -1,31 +1,156 @@
 // This file provides conversion between gamerplex math types and other libraries
 
-#[cfg(feature = "rapier3d")]
-pub mod rapier {
-    use crate::vector::Vector3;
-    use crate::quaternion::Quaternion;
-    use rapier3d::math as rapier_math;
-    
-    // Vector3 conversions
-    impl From<Vector3> for rapier_math::Vector<f32> {
-        fn from(v: Vector3) -> Self {
-            rapier_math::Vector::new(v.x, v.y, v.z)
+use rapier3d::na;
+
+use crate::vector::Vector3;
+use crate::quaternion::Quaternion;
+use crate::transforms::Transform;
+
+// Vector3 conversions
+impl From<Vector3> for na::Vector3<f32> {
+    fn from(v: Vector3) -> Self {
+        na::Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<na::Vector3<f32>> for Vector3 {
+    fn from(v: na::Vector3<f32>) -> Self {
+        Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<&na::Vector3<f32>> for Vector3 {
+    fn from(v: &na::Vector3<f32>) -> Self {
+        Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+// Points are positions, vectors are directions, both map onto Vector3
+impl From<Vector3> for na::Point3<f32> {
+    fn from(p: Vector3) -> Self {
+        na::Point3::new(p.x, p.y, p.z)
+    }
+}
+
+impl From<na::Point3<f32>> for Vector3 {
+    fn from(p: na::Point3<f32>) -> Self {
+        Vector3::new(p.x, p.y, p.z)
+    }
+}
+
+impl From<&na::Point3<f32>> for Vector3 {
+    fn from(p: &na::Point3<f32>) -> Self {
+        Vector3::new(p.x, p.y, p.z)
+    }
+}
+
+impl From<Vector3> for na::Translation3<f32> {
+    fn from(v: Vector3) -> Self {
+        na::Translation3::new(v.x, v.y, v.z)
+    }
+}
+
+// Quaternion conversions, nalgebra stores w first
+impl From<Quaternion> for na::UnitQuaternion<f32> {
+    fn from(q: Quaternion) -> Self {
+        na::UnitQuaternion::new_normalize(na::Quaternion::new(q.w, q.x, q.y, q.z))
+    }
+}
+
+impl From<na::UnitQuaternion<f32>> for Quaternion {
+    fn from(q: na::UnitQuaternion<f32>) -> Self {
+        Quaternion::new(q.i, q.j, q.k, q.w)
+    }
+}
+
+impl From<&na::UnitQuaternion<f32>> for Quaternion {
+    fn from(q: &na::UnitQuaternion<f32>) -> Self {
+        Quaternion::new(q.i, q.j, q.k, q.w)
+    }
+}
+
+// Isometries have no scale, it is dropped going in and comes back as ones
+impl From<Transform> for na::Isometry3<f32> {
+    fn from(t: Transform) -> Self {
+        na::Isometry3::from_parts(t.position.into(), t.rotation.into())
+    }
+}
+
+impl From<na::Isometry3<f32>> for Transform {
+    fn from(iso: na::Isometry3<f32>) -> Self {
+        Transform::new(iso.translation.vector.into(), iso.rotation.into(), Vector3::ones())
+    }
+}
+
+impl From<&na::Isometry3<f32>> for Transform {
+    fn from(iso: &na::Isometry3<f32>) -> Self {
+        Transform::new(iso.translation.vector.into(), iso.rotation.into(), Vector3::ones())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // small deterministic generator, good enough to sweep the rotation space
+    fn samples(count: usize) -> impl Iterator<Item = [f32; 4]> {
+        let mut state: u32 = 0x2545_f491;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as f32 / u32::MAX as f32 * 2.0 - 1.0
+        };
+        (0..count).map(move |_| [next(), next(), next(), next()])
+    }
+
+    fn same_rotation(a: Quaternion, b: Quaternion) -> bool {
+        // q and -q are the same rotation
+        let dot = a.x * b.x + a.y * b.y + a.z * b.z + a.w * b.w;
+        (dot.abs() - 1.0).abs() < 1e-5
+    }
+
+    #[test]
+    fn vectors_round_trip() {
+        for [x, y, z, _] in samples(1000) {
+            let v = Vector3::new(x * 100.0, y * 100.0, z * 100.0);
+            assert_eq!(Vector3::from(na::Vector3::from(v)), v);
+            assert_eq!(Vector3::from(na::Point3::from(v)), v);
         }
     }
-    
-    impl From<rapier_math::Vector<f32>> for Vector3 {
-        fn from(v: rapier_math::Vector<f32>) -> Self {
-            Vector3::new(v.x, v.y, v.z)
+
+    #[test]
+    fn quaternions_round_trip() {
+        for [x, y, z, w] in samples(1000) {
+            let q = Quaternion::new(x, y, z, w).normalize();
+            assert!(same_rotation(Quaternion::from(na::UnitQuaternion::from(q)), q));
         }
     }
-    
-    // Quaternion conversions
-    impl From<Quaternion> for rapier_math::Rotation<f32> {
-        fn from(q: Quaternion) -> Self {
-            rapier_math::Rotation::from_quaternion(
-                rapier_math::Quaternion::new(q.w, q.x, q.y, q.z)
-            )
+
+    #[test]
+    fn quaternions_rotate_like_nalgebra() {
+        for [x, y, z, angle] in samples(1000) {
+            let axis = Vector3::new(x, y, z).normalize();
+            let q = Quaternion::from_axis_angle(&axis, angle * std::f32::consts::PI);
+            let expected = na::UnitQuaternion::from_axis_angle(
+                &na::Unit::new_normalize(na::Vector3::from(axis)),
+                angle * std::f32::consts::PI,
+            );
+            assert!(same_rotation(q, Quaternion::from(expected)));
+        }
+    }
+
+    #[test]
+    fn isometries_round_trip() {
+        for [x, y, z, w] in samples(1000) {
+            let transform = Transform::new(
+                Vector3::new(w, x, y),
+                Quaternion::new(x, y, z, w).normalize(),
+                Vector3::ones(),
+            );
+            let back = Transform::from(na::Isometry3::from(transform.clone()));
+            assert!((back.position - transform.position).length() < 1e-6);
+            assert!(same_rotation(back.rotation, transform.rotation));
         }
     }
-    
-}
\ No newline at end of file
+}
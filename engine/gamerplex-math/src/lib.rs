@@ -1,12 +1,9 @@
 pub use vector::*;
 pub use quaternion::*;
 pub use transforms::*;
-pub use conversion::*;
 
 mod vector;
 mod quaternion;
 mod transforms;
+#[cfg(feature = "rapier")]
 mod conversion;
-
-#[cfg(feature = "debug")]
-pub mod debug;
\ No newline at end of file
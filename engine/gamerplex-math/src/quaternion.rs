@@ -1,6 +1,7 @@
 use crate::Vector3;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Quaternion {
     pub x: f32,
     pub y: f32,
@@ -2,6 +2,7 @@ use crate::vector::Vector3;
 use crate::quaternion::Quaternion;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transform {
     pub position: Vector3,
     pub rotation: Quaternion,
@@ -0,0 +1,89 @@
+// Steps a scene of stacked boxes single- and multi-threaded.
+//
+//   cargo bench -p gamerplex-physics --bench stacked_boxes
+//   cargo bench -p gamerplex-physics --bench stacked_boxes --features parallel
+
+use std::time::{Duration, Instant};
+
+use gamerplex_math::Vector3;
+use gamerplex_physics::{Body, BodyType, ColliderDef, ColliderShape, World};
+
+const DT: f32 = 1.0 / 60.0;
+const STEPS: u32 = 120;
+
+// `towers` x `towers` towers of `height` boxes, the bodies never sleep so every step does full work
+fn stacked_boxes(towers: u32, height: u32) -> World {
+    let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+
+    let ground = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+    world.add_collider(0, ground, &ColliderDef {
+        shape: ColliderShape::Box { half_extents: Vector3::new(200.0, 0.5, 200.0) },
+        ..Default::default()
+    }).unwrap();
+
+    let mut entity = 1;
+    for x in 0..towers {
+        for z in 0..towers {
+            for y in 0..height {
+                let body = world.add_rigid_body(entity, &Body {
+                    position: Vector3::new(x as f32 * 3.0, 1.0 + y as f32 * 1.01, z as f32 * 3.0),
+                    can_sleep: false,
+                    ..Default::default()
+                });
+                world.add_collider(entity, body, &ColliderDef::default()).unwrap();
+                entity += 1;
+            }
+        }
+    }
+
+    world
+}
+
+fn time_steps(mut step: impl FnMut()) -> Duration {
+    // let the towers settle into contact before measuring
+    for _ in 0..10 {
+        step();
+    }
+
+    let start = Instant::now();
+    for _ in 0..STEPS {
+        step();
+    }
+    start.elapsed() / STEPS
+}
+
+fn report(label: &str, per_step: Duration) {
+    println!("{:<40} {:>8.2} ms/step", label, per_step.as_secs_f64() * 1000.0);
+}
+
+fn main() {
+    // 4000 boxes in one world
+    let mut world = stacked_boxes(10, 40);
+    report("1 world, 4000 boxes", time_steps(|| world.step(DT)));
+
+    // 8 matches of 500 boxes, one after the other
+    let mut worlds: Vec<World> = (0..8).map(|_| stacked_boxes(5, 20)).collect();
+    report("8 worlds, 500 boxes each, in sequence", time_steps(|| {
+        for world in worlds.iter_mut() {
+            world.step(DT);
+        }
+    }));
+
+    #[cfg(feature = "parallel")]
+    {
+        let threads = std::thread::available_parallelism().map_or(4, |threads| threads.get());
+        let pool = gamerplex_physics::build_thread_pool(threads).unwrap();
+
+        let mut world = stacked_boxes(10, 40);
+        world.set_thread_pool(Some(pool.clone()));
+        report(&format!("1 world, 4000 boxes, pool of {}", threads), time_steps(|| world.step(DT)));
+
+        let mut worlds: Vec<World> = (0..8).map(|_| stacked_boxes(5, 20)).collect();
+        report(&format!("8 worlds, 500 boxes each, pool of {}", threads), time_steps(|| {
+            pool.install(|| gamerplex_physics::step_worlds(&mut worlds, DT));
+        }));
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    println!("enable the `parallel` feature for the multi-threaded numbers");
+}
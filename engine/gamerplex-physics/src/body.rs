@@ -1,25 +1,34 @@
 use gamerplex_math::{Quaternion, Vector3};
+use rapier3d::prelude::{MassProperties, RigidBodyHandle};
+use serde::{Deserialize, Serialize};
 
+use crate::integration::rapier;
+use crate::{EntityId, PhysicsError, World};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum BodyType {
     Dynamic, // laws of physics aplies to these body types
     Static, // immovable objs like mountains and terrains
     Kinematic // programatic movements not the laws of phyysics
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Body {
     pub body_type: BodyType,
     pub position: Vector3,
     pub rotation: Quaternion,
     pub linear_velocity: Vector3,
     pub angular_velocity: Vector3,
-    pub mass: f32,
+    pub mass: MassMode,
+    pub locked_axes: AxisLocks,
+    pub gravity_scale: f32,   // 0.0 ignores gravity, negative values fall upwards
+    pub dominance_group: i8,  // bodies in a higher group push lower ones without being pushed back
     pub linear_damping: f32,
     pub angular_damping: f32,
     pub can_sleep: bool,
-    pub ccd_enabled: bool, // Continuous collision detection
+    pub ccd_enabled: bool, // Continuous collision detection, sweeps the motion so fast bodies cannot tunnel
+    pub soft_ccd_prediction: f32, // looks this far ahead for predictive contacts, cheaper than ccd_enabled, 0.0 disables
 }
 
 impl Default for Body {
@@ -30,11 +39,295 @@ impl Default for Body {
             rotation: Quaternion::identity(),
             linear_velocity: Vector3::zeros(),
             angular_velocity: Vector3::zeros(),
-            mass: 1.0,
+            mass: MassMode::Computed,
+            locked_axes: AxisLocks::default(),
+            gravity_scale: 1.0,
+            dominance_group: 0,
             linear_damping: 0.0,
             angular_damping: 0.0,
             can_sleep: true,
             ccd_enabled: false,
+            soft_ccd_prediction: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MassMode {
+    Computed,   // from the density and shape of the attached colliders
+    Total(f32), // this mass no matter the colliders, inertia still follows their shapes
+    Custom {
+        mass: f32,
+        center_of_mass: Vector3,      // in the body's local space
+        principal_inertia: Vector3,   // inertia around the principal axes
+        inertia_frame: Quaternion,    // orientation of the principal axes in local space
+    },
+}
+
+// Locked axes are in world space, e.g. lock every rotation to keep a character upright
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AxisLocks {
+    pub translation_x: bool,
+    pub translation_y: bool,
+    pub translation_z: bool,
+    pub rotation_x: bool,
+    pub rotation_y: bool,
+    pub rotation_z: bool,
+}
+
+impl AxisLocks {
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn all_rotations() -> Self {
+        Self { rotation_x: true, rotation_y: true, rotation_z: true, ..Self::default() }
+    }
+}
+
+// What the solver actually uses, after colliders and the mass mode were combined
+#[derive(Clone, Debug)]
+pub struct BodyMassProperties {
+    pub mass: f32,
+    pub local_center_of_mass: Vector3,
+    pub world_center_of_mass: Vector3,
+    pub principal_inertia: Vector3,
+    pub inertia_frame: Quaternion,
+}
+
+impl World {
+    pub fn mass_properties(&self, entity: EntityId) -> Result<BodyMassProperties, PhysicsError> {
+        let body = self.rigid_body(entity)?;
+        let local = &body.mass_properties().local_mprops;
+
+        Ok(BodyMassProperties {
+            mass: local.mass(),
+            local_center_of_mass: Vector3::from(local.local_com),
+            world_center_of_mass: Vector3::from(body.center_of_mass()),
+            principal_inertia: Vector3::from(local.principal_inertia()),
+            inertia_frame: Quaternion::from(local.principal_inertia_local_frame),
+        })
+    }
+
+    pub fn set_mass_mode(&mut self, entity: EntityId, mode: MassMode) -> Result<(), PhysicsError> {
+        let handle = self.entity_body_map.get(&entity).ok_or(PhysicsError::NoRigidBody(entity))?.to_rapier_handle();
+
+        if mode == MassMode::Computed {
+            self.mass_modes.remove(&handle);
+        } else {
+            self.mass_modes.insert(handle, mode);
         }
+        self.refresh_mass_properties(handle);
+        Ok(())
+    }
+
+    pub fn set_axis_locks(&mut self, entity: EntityId, locks: AxisLocks) -> Result<(), PhysicsError> {
+        self.rigid_body_mut(entity)?.set_locked_axes(rapier::convert_axis_locks(&locks), true);
+        Ok(())
+    }
+
+    pub fn set_gravity_scale(&mut self, entity: EntityId, scale: f32) -> Result<(), PhysicsError> {
+        self.rigid_body_mut(entity)?.set_gravity_scale(scale, true);
+        Ok(())
     }
-}
\ No newline at end of file
+
+    pub fn set_dominance_group(&mut self, entity: EntityId, group: i8) -> Result<(), PhysicsError> {
+        self.rigid_body_mut(entity)?.set_dominance_group(group);
+        Ok(())
+    }
+
+    pub fn set_ccd_enabled(&mut self, entity: EntityId, enabled: bool) -> Result<(), PhysicsError> {
+        self.rigid_body_mut(entity)?.enable_ccd(enabled);
+        Ok(())
+    }
+
+    pub fn set_soft_ccd_prediction(&mut self, entity: EntityId, distance: f32) -> Result<(), PhysicsError> {
+        self.rigid_body_mut(entity)?.set_soft_ccd_prediction(distance);
+        Ok(())
+    }
+
+    // Bodies that moved fast enough for CCD to run on them during the last `step`, sorted
+    pub fn ccd_active_bodies(&self) -> &[EntityId] {
+        &self.ccd_active_bodies
+    }
+
+    // Called after every substep, Rapier only flags the bodies for the substep it just ran
+    pub(crate) fn collect_ccd_active_bodies(&mut self) {
+        for handle in self.island_manager.active_dynamic_bodies() {
+            if !self.rigid_body_set[*handle].is_ccd_active() {
+                continue;
+            }
+            if let Some(entity) = self.body_entity_map.get(handle) {
+                if let Err(index) = self.ccd_active_bodies.binary_search(entity) {
+                    self.ccd_active_bodies.insert(index, *entity);
+                }
+            }
+        }
+    }
+
+    // Rapier always adds the colliders' mass on top of the body's own, so overrides
+    // are re-derived whenever the body's colliders change
+    pub(crate) fn refresh_mass_properties(&mut self, handle: RigidBodyHandle) {
+        let Some(body) = self.rigid_body_set.get_mut(handle) else {
+            return;
+        };
+
+        // colliders of a custom body stop contributing, they get their density back
+        // once the body leaves the custom mode
+        let custom = matches!(self.mass_modes.get(&handle), Some(MassMode::Custom { .. }));
+        for collider_handle in body.colliders() {
+            let Some(collider) = self.collider_set.get_mut(*collider_handle) else {
+                continue;
+            };
+            if custom {
+                self.custom_mass_densities.entry(*collider_handle).or_insert_with(|| collider.density());
+                collider.set_density(0.0);
+            } else if let Some(density) = self.custom_mass_densities.remove(collider_handle) {
+                collider.set_density(density);
+            }
+        }
+
+        match self.mass_modes.get(&handle) {
+            None | Some(MassMode::Computed) => {
+                body.set_additional_mass_properties(MassProperties::default(), true);
+            },
+            Some(MassMode::Total(mass)) => {
+                let collider_mass: f32 = body.colliders()
+                    .iter()
+                    .filter_map(|collider| self.collider_set.get(*collider))
+                    .map(|collider| collider.mass())
+                    .sum();
+                // may be negative, rapier then scales the colliders' inertia down
+                body.set_additional_mass(mass - collider_mass, true);
+            },
+            Some(MassMode::Custom { mass, center_of_mass, principal_inertia, inertia_frame }) => {
+                body.set_additional_mass_properties(MassProperties::with_principal_inertia_frame(
+                    (*center_of_mass).into(),
+                    *mass,
+                    (*principal_inertia).into(),
+                    (*inertia_frame).into(),
+                ), true);
+            },
+        }
+
+        // keep the getters accurate before the next step
+        body.recompute_mass_properties_from_colliders(&self.collider_set);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColliderDef, ColliderShape};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn close(a: Vector3, b: Vector3) -> bool {
+        (a - b).length() < 1e-3
+    }
+
+    fn densities(world: &World, entity: EntityId) -> Vec<f32> {
+        world.colliders(entity)
+            .iter()
+            .map(|collider| world.collider_set[collider.to_rapier_handle()].density())
+            .collect()
+    }
+
+    #[test]
+    fn mass_modes_switch_back_and_forth() {
+        let mut world = World::new(Vector3::zeros());
+        let body = world.add_rigid_body(1, &Body::default());
+        world.add_collider(1, body, &ColliderDef { density: 2.0, ..Default::default() }).unwrap();
+        assert!((world.mass_properties(1).unwrap().mass - 2.0).abs() < 1e-4);
+
+        world.set_mass_mode(1, MassMode::Total(10.0)).unwrap();
+        assert!((world.mass_properties(1).unwrap().mass - 10.0).abs() < 1e-4);
+
+        world.set_mass_mode(1, MassMode::Custom {
+            mass: 5.0,
+            center_of_mass: Vector3::new(0.0, 1.0, 0.0),
+            principal_inertia: Vector3::new(1.0, 2.0, 3.0),
+            inertia_frame: Quaternion::identity(),
+        }).unwrap();
+        // colliders added later do not count either
+        world.add_collider(1, body, &ColliderDef { density: 2.0, position: Vector3::unit_x(), ..Default::default() }).unwrap();
+        let custom = world.mass_properties(1).unwrap();
+        assert!((custom.mass - 5.0).abs() < 1e-4);
+        assert!(close(custom.local_center_of_mass, Vector3::new(0.0, 1.0, 0.0)));
+        assert!(close(custom.principal_inertia, Vector3::new(1.0, 2.0, 3.0)));
+        // the colliders stop adding mass while the original densities are kept aside
+        assert_eq!(densities(&world, 1), vec![0.0, 0.0]);
+
+        world.set_mass_mode(1, MassMode::Computed).unwrap();
+        let computed = world.mass_properties(1).unwrap();
+        assert!((computed.mass - 4.0).abs() < 1e-4);
+        assert!(close(computed.local_center_of_mass, Vector3::new(0.5, 0.0, 0.0)));
+        assert_eq!(densities(&world, 1), vec![2.0, 2.0]);
+    }
+
+    #[test]
+    fn locks_and_gravity_scale() {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+        for (entity, body) in [
+            (1, Body::default()),
+            (2, Body { gravity_scale: 0.0, ..Default::default() }),
+            (3, Body { locked_axes: AxisLocks { translation_y: true, ..AxisLocks::all_rotations() }, ..Default::default() }),
+        ] {
+            let body = Body { position: Vector3::new(entity as f32 * 3.0, 0.0, 0.0), ..body };
+            let handle = world.add_rigid_body(entity, &body);
+            world.add_collider(entity, handle, &ColliderDef::default()).unwrap();
+            world.apply_torque_impulse(entity, Vector3::new(0.0, 0.0, 1.0)).unwrap();
+        }
+
+        for _ in 0..30 {
+            world.step(DT);
+        }
+        assert!(world.rigid_body(1).unwrap().translation().y < -1.0);
+        assert!(world.rigid_body(2).unwrap().translation().y.abs() < 1e-4);
+        assert!(world.rigid_body(3).unwrap().translation().y.abs() < 1e-4);
+        assert!(world.angular_velocity(1).unwrap().z > 0.0);
+        assert_eq!(world.angular_velocity(3).unwrap(), Vector3::zeros());
+
+        // locking an axis keeps the velocity the body already has along it
+        world.set_axis_locks(1, AxisLocks { translation_y: true, ..Default::default() }).unwrap();
+        world.set_linear_velocity(1, Vector3::zeros()).unwrap();
+        world.set_gravity_scale(2, -1.0).unwrap();
+        let before = world.rigid_body(1).unwrap().translation().y;
+        for _ in 0..30 {
+            world.step(DT);
+        }
+        assert!((world.rigid_body(1).unwrap().translation().y - before).abs() < 1e-4);
+        assert!(world.rigid_body(2).unwrap().translation().y > 1.0);
+    }
+
+    // Slides a box into a resting one, returns the speed it keeps after the hit
+    fn speed_after_hit(dominance_group: i8) -> f32 {
+        let mut world = World::new(Vector3::zeros());
+        let pusher = world.add_rigid_body(1, &Body {
+            position: Vector3::new(-2.0, 0.0, 0.0),
+            linear_velocity: Vector3::new(4.0, 0.0, 0.0),
+            ..Default::default()
+        });
+        world.add_collider(1, pusher, &ColliderDef::default()).unwrap();
+        let target = world.add_rigid_body(2, &Body::default());
+        world.add_collider(2, target, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(0.5, 0.5, 0.5) },
+            density: 10.0,
+            ..Default::default()
+        }).unwrap();
+        world.set_dominance_group(1, dominance_group).unwrap();
+
+        for _ in 0..60 {
+            world.step(DT);
+        }
+        assert!(world.linear_velocity(2).unwrap().x > 0.0);
+        world.linear_velocity(1).unwrap().x
+    }
+
+    #[test]
+    fn dominant_bodies_are_not_pushed_back() {
+        assert!(speed_after_hit(0) < 1.0);
+        assert!((speed_after_hit(1) - 4.0).abs() < 1e-3);
+    }
+}
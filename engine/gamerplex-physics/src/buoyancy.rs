@@ -0,0 +1,310 @@
+use std::f32::consts::PI;
+
+use rapier3d::prelude::{ConvexPolyhedron, Isometry, Point, Shape, Vector};
+
+// The part of a shape below a horizontal water surface, in world space
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct Submerged {
+    pub volume: f32,
+    pub centroid: Point<f32>,
+}
+
+impl Submerged {
+    fn merged(self, other: Submerged) -> Submerged {
+        let volume = self.volume + other.volume;
+        if volume <= f32::EPSILON {
+            return Submerged::default();
+        }
+        let centroid = (self.centroid.coords * self.volume + other.centroid.coords * other.volume) / volume;
+        Submerged { volume, centroid: centroid.into() }
+    }
+}
+
+// Points p with up · p <= surface are under water, `up` has unit length
+pub(crate) fn submerged_part(shape: &dyn Shape, position: &Isometry<f32>, up: &Vector<f32>, surface: f32) -> Submerged {
+    let aabb = shape.compute_aabb(position);
+    let heights = aabb.vertices().map(|corner| up.dot(&corner.coords));
+    if heights.iter().all(|height| *height >= surface) {
+        return Submerged::default();
+    }
+    if heights.iter().all(|height| *height <= surface) {
+        let props = shape.mass_properties(1.0);
+        return Submerged { volume: props.mass(), centroid: position * props.local_com };
+    }
+
+    if let Some(ball) = shape.as_ball() {
+        return ball_part(ball.radius, position.translation.vector.into(), up, surface);
+    }
+    if let Some(cuboid) = shape.as_cuboid() {
+        let corners: Vec<_> = (0..8)
+            .map(|i| {
+                let sign = |bit: usize| if i & (1 << bit) == 0 { -1.0 } else { 1.0 };
+                let he = cuboid.half_extents;
+                position * Point::new(sign(0) * he.x, sign(1) * he.y, sign(2) * he.z)
+            })
+            .collect();
+        // segments between any two corners stay inside the box, so cutting all of
+        // them gives the same clipped hull as cutting the 12 edges
+        let segments = (0..8).flat_map(|a| (a + 1..8).map(move |b| (a, b)));
+        return clipped_polytope(&corners, segments, up, surface);
+    }
+    if let Some(polyhedron) = shape.as_convex_polyhedron() {
+        let points: Vec<_> = polyhedron.points().iter().map(|point| position * point).collect();
+        let edges = polyhedron.edges().iter().map(|edge| (edge.vertices.x as usize, edge.vertices.y as usize));
+        return clipped_polytope(&points, edges, up, surface);
+    }
+    if let Some(capsule) = shape.as_capsule() {
+        let (a, b) = (position * capsule.segment.a, position * capsule.segment.b);
+        let (r, length) = (capsule.radius, (b - a).norm());
+        if length <= f32::EPSILON {
+            return ball_part(r, a, up, surface);
+        }
+        let axis = (b - a) / length;
+        let radius = |x: f32| {
+            let outside = (r - x).max(x - r - length).max(0.0);
+            (r * r - outside * outside).max(0.0).sqrt()
+        };
+        return revolution_part(a - axis * r, axis, &[0.0, r, r + length, 2.0 * r + length], radius, shape, up, surface);
+    }
+    if let Some(cylinder) = shape.as_cylinder() {
+        let (half_height, r) = (cylinder.half_height, cylinder.radius);
+        let axis = position.rotation * Vector::y();
+        let base = position * Point::new(0.0, -half_height, 0.0);
+        return revolution_part(base, axis, &[0.0, 2.0 * half_height], |_| r, shape, up, surface);
+    }
+    if let Some(cone) = shape.as_cone() {
+        // the base is at -half_height, the apex at +half_height
+        let (height, r) = (2.0 * cone.half_height, cone.radius);
+        let axis = position.rotation * Vector::y();
+        let base = position * Point::new(0.0, -cone.half_height, 0.0);
+        return revolution_part(base, axis, &[0.0, height], |x| r * (1.0 - x / height), shape, up, surface);
+    }
+    if let Some(compound) = shape.as_compound() {
+        return compound.shapes()
+            .iter()
+            .map(|(part_position, part)| submerged_part(part.as_ref(), &(position * part_position), up, surface))
+            .fold(Submerged::default(), Submerged::merged);
+    }
+
+    sampled_part(shape, position, up, surface, 12)
+}
+
+// Spherical cap below the surface
+fn ball_part(r: f32, center: Point<f32>, up: &Vector<f32>, surface: f32) -> Submerged {
+    let height = (r + surface - up.dot(&center.coords)).clamp(0.0, 2.0 * r);
+    if height <= 0.0 {
+        return Submerged::default();
+    }
+    let volume = PI * height * height * (3.0 * r - height) / 3.0;
+    let below_center = 3.0 * (2.0 * r - height).powi(2) / (4.0 * (3.0 * r - height));
+    Submerged { volume, centroid: center - up * below_center }
+}
+
+// Hull of the points under water and of the points where the segments between
+// them cross the surface
+fn clipped_polytope(
+    points: &[Point<f32>],
+    segments: impl Iterator<Item = (usize, usize)>,
+    up: &Vector<f32>,
+    surface: f32,
+) -> Submerged {
+    let depth = |point: &Point<f32>| surface - up.dot(&point.coords);
+    let mut clipped: Vec<_> = points.iter().copied().filter(|point| depth(point) >= 0.0).collect();
+    for (a, b) in segments {
+        let (depth_a, depth_b) = (depth(&points[a]), depth(&points[b]));
+        if (depth_a < 0.0) != (depth_b < 0.0) {
+            clipped.push(points[a] + (points[b] - points[a]) * (depth_a / (depth_a - depth_b)));
+        }
+    }
+
+    // None when only a sliver is under water
+    ConvexPolyhedron::from_convex_hull(&clipped)
+        .map(|hull| {
+            let props = hull.mass_properties(1.0);
+            Submerged { volume: props.mass(), centroid: props.local_com }
+        })
+        .unwrap_or_default()
+}
+
+// Shapes of revolution cut into slices along their axis, every slice is a disc whose
+// part under water is a circular segment. `breaks` are the axial distances from `base`
+// where `radius` changes its formula.
+#[allow(clippy::too_many_arguments)]
+fn revolution_part(
+    base: Point<f32>,
+    axis: Vector<f32>,
+    breaks: &[f32],
+    radius: impl Fn(f32) -> f32,
+    shape: &dyn Shape,
+    up: &Vector<f32>,
+    surface: f32,
+) -> Submerged {
+    const SLICES: usize = 16; // per piece, even for Simpson's rule
+
+    // direction inside the discs that points up the most
+    let slope = up - axis * up.dot(&axis);
+    let sin = slope.norm();
+    let inside_up = if sin > 1e-6 { slope / sin } else { Vector::zeros() };
+
+    // also split where the axis crosses the surface, so upright bodies integrate exactly
+    let mut pieces = breaks.to_vec();
+    if up.dot(&axis).abs() > 1e-6 {
+        let crossing = (surface - up.dot(&base.coords)) / up.dot(&axis);
+        if crossing > breaks[0] && crossing < breaks[breaks.len() - 1] {
+            pieces.push(crossing);
+            pieces.sort_by(f32::total_cmp);
+        }
+    }
+
+    let (mut full, mut wet, mut moment) = (0.0, 0.0, Vector::zeros());
+    for piece in pieces.windows(2) {
+        let step = (piece[1] - piece[0]) / SLICES as f32;
+        for i in 0..=SLICES {
+            let weight = step / 3.0 * if i == 0 || i == SLICES { 1.0 } else if i % 2 == 1 { 4.0 } else { 2.0 };
+            let x = piece[0] + step * i as f32;
+            let center = base + axis * x;
+            let r = radius(x);
+            // measured a hair inside the piece, a slice exactly at the crossing belongs to its piece's side
+            let probe = center + axis * ((piece[0] + piece[1]) / 2.0 - x) * 1e-3;
+            let (area, offset) = disc_part(r, surface - up.dot(&probe.coords), sin);
+
+            full += weight * PI * r * r;
+            wet += weight * area;
+            moment += (center.coords + inside_up * offset) * (weight * area);
+        }
+    }
+
+    if wet <= f32::EPSILON || full <= f32::EPSILON {
+        return Submerged::default();
+    }
+    // scaling by the exact volume removes most of the integration error
+    let volume = shape.mass_properties(1.0).mass() * wet / full;
+    Submerged { volume, centroid: (moment / wet).into() }
+}
+
+// Area of a disc of radius r below the surface, whose center is `depth` under it, and
+// how far the centroid of that area is from the center along the in-disc up direction
+fn disc_part(r: f32, depth: f32, sin: f32) -> (f32, f32) {
+    if r <= 0.0 {
+        return (0.0, 0.0);
+    }
+    if sin <= 1e-6 {
+        return if depth >= 0.0 { (PI * r * r, 0.0) } else { (0.0, 0.0) };
+    }
+
+    // points x of the disc are wet while x <= k along the in-disc up direction
+    let k = depth / sin;
+    if k >= r {
+        return (PI * r * r, 0.0);
+    }
+    if k <= -r {
+        return (0.0, 0.0);
+    }
+    let half_chord = (r * r - k * k).sqrt();
+    let dry = r * r * (k / r).acos() - k * half_chord;
+    let wet = PI * r * r - dry;
+    if wet <= f32::EPSILON {
+        return (0.0, 0.0);
+    }
+    (wet, -2.0 / 3.0 * half_chord.powi(3) / wet)
+}
+
+// Rounded shapes and meshes, counts the points of a samples³ grid inside the shape.
+// Cells cut by the surface count with the part of their height under water.
+fn sampled_part(shape: &dyn Shape, position: &Isometry<f32>, up: &Vector<f32>, surface: f32, samples: usize) -> Submerged {
+    let aabb = shape.compute_local_aabb();
+    let cell = aabb.extents() / samples as f32;
+    let cell_height = cell.dot(&position.rotation.inverse_transform_vector(up).abs()).max(f32::EPSILON);
+    let (mut inside, mut wet, mut sum) = (0, 0.0, Vector::zeros());
+
+    for i in 0..samples.pow(3) {
+        let index = Vector::new(i % samples, i / samples % samples, i / (samples * samples)).cast::<f32>();
+        let local = aabb.mins + cell.component_mul(&index.add_scalar(0.5));
+        if !shape.contains_local_point(&local) {
+            continue;
+        }
+        inside += 1;
+        let point = position * local;
+        let fraction = ((surface - up.dot(&point.coords)) / cell_height + 0.5).clamp(0.0, 1.0);
+        wet += fraction;
+        sum += point.coords * fraction;
+    }
+
+    if wet <= 0.0 {
+        return Submerged::default();
+    }
+    let volume = shape.mass_properties(1.0).mass() * wet / inside as f32;
+    Submerged { volume, centroid: (sum / wet).into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rapier3d::prelude::{Rotation, SharedShape};
+
+    const UP: Vector<f32> = Vector::new(0.0, 1.0, 0.0);
+
+    fn tilted(angle: f32) -> Isometry<f32> {
+        Isometry::from_parts(Vector::new(0.3, 1.0, -0.2).into(), Rotation::from_euler_angles(angle, 0.0, angle * 0.5))
+    }
+
+    // volumes within 2% of the whole shape's volume
+    fn close(a: Submerged, b: Submerged, shape: &SharedShape) -> bool {
+        let total = shape.mass_properties(1.0).mass();
+        (a.volume - b.volume).abs() <= 0.02 * total && (b.volume < 0.02 * total || (a.centroid - b.centroid).norm() <= 0.02)
+    }
+
+    #[test]
+    fn balls_cut_into_caps() {
+        let ball = SharedShape::ball(0.5);
+        let position = Isometry::translation(0.0, 2.0, 0.0);
+        let half = submerged_part(ball.as_ref(), &position, &UP, 2.0);
+        assert!((half.volume - PI / 12.0).abs() < 1e-5);
+        // the centroid of a half ball is 3/8 r below its flat side
+        assert!((half.centroid.y - (2.0 - 3.0 / 16.0)).abs() < 1e-5);
+
+        assert_eq!(submerged_part(ball.as_ref(), &position, &UP, 1.0).volume, 0.0);
+        assert!((submerged_part(ball.as_ref(), &position, &UP, 3.0).volume - PI / 6.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn boxes_are_clipped_exactly() {
+        let cuboid = SharedShape::cuboid(0.5, 1.0, 1.5);
+        let upright = submerged_part(cuboid.as_ref(), &Isometry::translation(0.0, 1.0, 0.0), &UP, 1.5);
+        assert!((upright.volume - 4.5).abs() < 1e-4);
+        assert!((upright.centroid - Point::new(0.0, 0.75, 0.0)).norm() < 1e-4);
+
+        // a box standing on one edge: the wet part is a triangular prism
+        let edge = Isometry::from_parts(Vector::zeros().into(), Rotation::from_euler_angles(0.0, 0.0, PI / 4.0));
+        let unit = SharedShape::cuboid(0.5, 0.5, 0.5);
+        let wedge = submerged_part(unit.as_ref(), &edge, &UP, -0.5_f32.sqrt() + 0.2);
+        assert!((wedge.volume - 0.04).abs() < 1e-4, "{:?}", wedge);
+    }
+
+    // The analytic paths agree with counting the points of a fine grid
+    #[test]
+    fn analytic_parts_match_sampling() {
+        let shapes = [
+            SharedShape::cuboid(0.4, 0.7, 0.3),
+            SharedShape::capsule_y(0.6, 0.3),
+            SharedShape::cylinder(0.5, 0.4),
+            SharedShape::cone(0.5, 0.4),
+        ];
+        for shape in &shapes {
+            for angle in [0.0, 0.9] {
+                for surface in [0.7, 1.2] {
+                    let position = tilted(angle);
+                    let exact = submerged_part(shape.as_ref(), &position, &UP, surface);
+                    let sampled = sampled_part(shape.as_ref(), &position, &UP, surface, 40);
+                    assert!(close(exact, sampled, shape), "{:?} at {} / {}: {:?} vs {:?}", shape.shape_type(), angle, surface, exact, sampled);
+                }
+            }
+        }
+
+        // the coarse grid used for other shapes is still in the right ballpark
+        let ball = SharedShape::ball(0.5);
+        let coarse = sampled_part(ball.as_ref(), &tilted(0.0), &UP, 0.8, 12);
+        let exact = submerged_part(ball.as_ref(), &tilted(0.0), &UP, 0.8);
+        assert!((coarse.volume - exact.volume).abs() < 0.05 * exact.volume, "{:?} vs {:?}", coarse, exact);
+    }
+}
@@ -0,0 +1,271 @@
+use gamerplex_math::Vector3;
+use rapier3d::control::CharacterCollision;
+use rapier3d::prelude::{ColliderHandle as RapierColliderHandle, Isometry, QueryFilter as RapierQueryFilter, Vector};
+use serde::{Deserialize, Serialize};
+
+use crate::integration::rapier;
+use crate::{EntityId, PhysicsError, World};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CharacterControllerDef {
+    pub up: Vector3,
+    pub offset: f32,                // gap kept between the capsule and obstacles
+    pub max_slope_climb_angle: f32, // radians, steeper slopes block the character
+    pub min_slope_slide_angle: f32, // radians, the character slides down steeper slopes
+    pub step_height: Option<f32>,   // max height of steps climbed automatically, None disables
+    pub step_min_width: f32,        // min free space on top of a step
+    pub snap_to_ground: Option<f32>, // keeps the character glued to the ground when walking down
+    pub slide: bool,                // slide along walls instead of stopping at them
+    pub push_dynamic_bodies: bool,
+    pub mass: f32,                  // used to push dynamic bodies
+}
+
+impl Default for CharacterControllerDef {
+    fn default() -> Self {
+        Self {
+            up: Vector3::unit_y(),
+            offset: 0.01,
+            max_slope_climb_angle: 45.0_f32.to_radians(),
+            min_slope_slide_angle: 30.0_f32.to_radians(),
+            step_height: Some(0.3),
+            step_min_width: 0.2,
+            snap_to_ground: Some(0.2),
+            slide: true,
+            push_dynamic_bodies: true,
+            mass: 80.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CharacterMovement {
+    pub translation: Vector3,       // what the character actually moved
+    pub grounded: bool,
+    pub sliding_down_slope: bool,
+    pub touched: Vec<EntityId>,     // entities hit during the move
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CharacterController {
+    pub def: CharacterControllerDef,
+    pub collider: RapierColliderHandle,
+    pub grounded: bool,
+}
+
+impl World {
+    // The entity needs a kinematic body with a capsule collider added through add_collider
+    pub fn add_character_controller(&mut self, entity: EntityId, def: &CharacterControllerDef) -> Result<(), PhysicsError> {
+        let body = self.rigid_body(entity)?;
+        if !body.is_kinematic() {
+            return Err(PhysicsError::BodyNotKinematic(entity));
+        }
+
+        let collider = body.colliders()
+            .iter()
+            .copied()
+            .find(|handle| self.collider_set[*handle].shape().as_capsule().is_some())
+            .ok_or(PhysicsError::NoCapsuleCollider(entity))?;
+
+        self.character_controllers.insert(entity, CharacterController {
+            def: def.clone(),
+            collider,
+            grounded: false,
+        });
+        Ok(())
+    }
+
+    pub fn remove_character_controller(&mut self, entity: EntityId) -> bool {
+        self.character_controllers.remove(&entity).is_some()
+    }
+
+    // Moves the character as far as possible along `desired_translation`. The body
+    // reaches the new position during the next simulation step, calls made before
+    // that step add up.
+    pub fn move_character(&mut self, entity: EntityId, desired_translation: Vector3, dt: f32) -> Result<CharacterMovement, PhysicsError> {
+        let body_handle = self.entity_body_map.get(&entity).ok_or(PhysicsError::NoRigidBody(entity))?.to_rapier_handle();
+        let controller = self.character_controllers.get(&entity).ok_or(PhysicsError::NoCharacterController(entity))?;
+        let collider = self.collider_set.get(controller.collider).ok_or(PhysicsError::NoCapsuleCollider(entity))?;
+        // start from where earlier calls already sent the body
+        let body_position = *self.rigid_body_set[body_handle].next_position();
+        let shape_position = body_position * collider.position_wrt_parent().copied().unwrap_or_else(Isometry::identity);
+
+        let rapier_controller = rapier::create_character_controller(&controller.def);
+        let filter = RapierQueryFilter::new()
+            .exclude_rigid_body(body_handle)
+            .exclude_sensors()
+            .groups(collider.collision_groups());
+
+        let mut collisions: Vec<CharacterCollision> = Vec::new();
+        let movement = rapier_controller.move_shape(
+            dt,
+            &self.rigid_body_set,
+            &self.collider_set,
+            &self.query_pipeline,
+            collider.shape(),
+            &shape_position,
+            Vector::from(desired_translation),
+            filter,
+            |collision| collisions.push(collision),
+        );
+
+        if controller.def.push_dynamic_bodies {
+            rapier_controller.solve_character_collision_impulses(
+                dt,
+                &mut self.rigid_body_set,
+                &self.collider_set,
+                &self.query_pipeline,
+                collider.shape(),
+                controller.def.mass,
+                &collisions,
+                filter,
+            );
+        }
+
+        self.rigid_body_set[body_handle].set_next_kinematic_translation(body_position.translation.vector + movement.translation);
+
+        if let Some(controller) = self.character_controllers.get_mut(&entity) {
+            controller.grounded = movement.grounded;
+        }
+
+        let mut touched: Vec<EntityId> = collisions.iter()
+            .filter_map(|collision| self.collider_entity(collision.handle))
+            .collect();
+        touched.sort_unstable();
+        touched.dedup();
+
+        Ok(CharacterMovement {
+            translation: Vector3::from(movement.translation),
+            grounded: movement.grounded,
+            sliding_down_slope: movement.is_sliding_down_slope,
+            touched,
+        })
+    }
+
+    // Result of the last move_character call
+    pub fn is_character_grounded(&self, entity: EntityId) -> Result<bool, PhysicsError> {
+        self.character_controllers
+            .get(&entity)
+            .map(|controller| controller.grounded)
+            .ok_or(PhysicsError::NoCharacterController(entity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, ColliderShape};
+    use gamerplex_math::Quaternion;
+
+    const DT: f32 = 1.0 / 60.0;
+    const START_HEIGHT: f32 = 1.32; // capsule bottom just above the ground
+
+    fn world_with_character() -> World {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+        let ground = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, ground, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(20.0, 0.5, 20.0) },
+            ..Default::default()
+        }).unwrap();
+
+        let character = world.add_rigid_body(1, &Body {
+            body_type: BodyType::Kinematic,
+            position: Vector3::new(0.0, START_HEIGHT, 0.0),
+            ..Default::default()
+        });
+        world.add_collider(1, character, &ColliderDef {
+            shape: ColliderShape::Capsule { height: 1.0, radius: 0.3 },
+            ..Default::default()
+        }).unwrap();
+        world.add_character_controller(1, &CharacterControllerDef::default()).unwrap();
+        world.update_query_pipeline();
+        world
+    }
+
+    fn add_static_box(world: &mut World, entity: EntityId, position: Vector3, rotation: Quaternion, half_extents: Vector3) {
+        let body = world.add_rigid_body(entity, &Body { body_type: BodyType::Static, position, rotation, ..Default::default() });
+        world.add_collider(entity, body, &ColliderDef { shape: ColliderShape::Box { half_extents }, ..Default::default() }).unwrap();
+        world.update_query_pipeline();
+    }
+
+    // Walks along +x at 3 m/s while pressing down, returns where the character ended up
+    fn walk(world: &mut World, frames: usize) -> Vector3 {
+        for _ in 0..frames {
+            world.move_character(1, Vector3::new(3.0 * DT, -0.02, 0.0), DT).unwrap();
+            world.step(DT);
+        }
+        Vector3::from(*world.rigid_body(1).unwrap().translation())
+    }
+
+    #[test]
+    fn grounded_and_repeated_moves() {
+        let mut world = world_with_character();
+        assert!(!world.is_character_grounded(1).unwrap());
+
+        let movement = world.move_character(1, Vector3::new(0.0, -0.1, 0.0), DT).unwrap();
+        assert!(movement.grounded);
+        assert!(world.is_character_grounded(1).unwrap());
+        assert_eq!(movement.touched, vec![0]);
+        world.step(DT);
+
+        // frames without a substep must not lose the earlier moves
+        let start = world.rigid_body(1).unwrap().translation().x;
+        world.move_character(1, Vector3::new(0.1, 0.0, 0.0), DT).unwrap();
+        world.move_character(1, Vector3::new(0.1, 0.0, 0.0), DT).unwrap();
+        world.step(DT);
+        assert!((world.rigid_body(1).unwrap().translation().x - start - 0.2).abs() < 1e-3);
+
+        let movement = world.move_character(1, Vector3::new(0.0, 1.0, 0.0), DT).unwrap();
+        assert!(!movement.grounded);
+    }
+
+    #[test]
+    fn slope_limits() {
+        let ramp = |angle: f32| {
+            let mut world = world_with_character();
+            let rotation = Quaternion::from_axis_angle(&Vector3::unit_z(), angle.to_radians());
+            add_static_box(&mut world, 2, Vector3::new(5.0, 0.5, 0.0), rotation, Vector3::new(4.0, 0.2, 4.0));
+            walk(&mut world, 180)
+        };
+
+        let gentle = ramp(20.0);
+        assert!(gentle.y > START_HEIGHT + 1.0);
+
+        let steep = ramp(60.0);
+        assert!(steep.x < 5.0);
+        assert!(steep.y < START_HEIGHT + 0.5);
+    }
+
+    #[test]
+    fn climbs_low_steps_only() {
+        let mut world = world_with_character();
+        add_static_box(&mut world, 2, Vector3::new(2.0, 0.6, 0.0), Quaternion::identity(), Vector3::new(0.5, 0.1, 4.0));
+        let position = walk(&mut world, 40);
+        assert!(position.x > 1.6);
+        assert!(position.y > START_HEIGHT + 0.15);
+
+        let mut world = world_with_character();
+        add_static_box(&mut world, 2, Vector3::new(2.0, 0.9, 0.0), Quaternion::identity(), Vector3::new(0.5, 0.4, 4.0));
+        let position = walk(&mut world, 60);
+        assert!(position.x < 1.5);
+        assert!(position.y < START_HEIGHT + 0.05);
+    }
+
+    #[test]
+    fn pushes_dynamic_bodies() {
+        let mut world = world_with_character();
+        let crate_body = world.add_rigid_body(2, &Body { position: Vector3::new(1.5, 0.8, 0.0), ..Default::default() });
+        world.add_collider(2, crate_body, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(0.3, 0.3, 0.3) },
+            ..Default::default()
+        }).unwrap();
+        world.update_query_pipeline();
+
+        let mut touched = Vec::new();
+        for _ in 0..60 {
+            touched.extend(world.move_character(1, Vector3::new(3.0 * DT, -0.02, 0.0), DT).unwrap().touched);
+            world.step(DT);
+        }
+        assert!(touched.contains(&2));
+        assert!(world.rigid_body(2).unwrap().translation().x > 1.6);
+    }
+}
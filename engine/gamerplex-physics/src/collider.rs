@@ -1,24 +1,52 @@
 use gamerplex_math::{Quaternion, Vector3};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+use crate::filter::CollisionGroups;
+use crate::material::MaterialId;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ColliderShape {
     Sphere { radius: f32 },
     Box { half_extents: Vector3 },
     Capsule { height: f32, radius: f32 },
     Cylinder { height: f32, radius: f32 },
     ConvexHull { points: Vec<Vector3> },
+    Cone { height: f32, radius: f32 },
+    Segment { a: Vector3, b: Vector3 },
+    // rounded shapes keep their outer size, the border radius is carved out of it
+    RoundBox { half_extents: Vector3, border_radius: f32 },
+    RoundCylinder { height: f32, radius: f32, border_radius: f32 },
+    RoundCone { height: f32, radius: f32, border_radius: f32 },
+    // static level geometry, triangles are not expected to form a closed volume
+    TriMesh { vertices: Vec<Vector3>, indices: Vec<[u32; 3]> },
+    // row-major grid of heights, spans scale.x by scale.z and heights are multiplied by scale.y
+    HeightField { heights: Vec<f32>, rows: usize, columns: usize, scale: Vector3 },
+    Compound { parts: Vec<CompoundPart> },
+    // splits an arbitrary mesh into convex hulls, for dynamic props
+    ConvexDecomposition { vertices: Vec<Vector3>, indices: Vec<[u32; 3]> },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompoundPart {
+    pub shape: ColliderShape,  // must not be a TriMesh, HeightField, Compound or ConvexDecomposition
+    pub position: Vector3,     // offset inside the compound
+    pub rotation: Quaternion,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ColliderDef {
     pub shape: ColliderShape,
     pub position: Vector3,      //offset from rigid body
     pub rotation: Quaternion,  // rotation relative to rigid body
     pub density: f32,   // used to calculate mass
-    pub friction: f32,  // 0.0 to 1.0 -> standard
-    pub restitution: f32, // bounciness, 0.0 to 1.0
+    pub material: MaterialId, // friction, restitution and surface tag, see World::register_material
     pub is_sensor: bool,  // detects but doesn't collide
-    pub collision_groups: u32, // filtering
+    pub collision_groups: CollisionGroups, // which colliders this one interacts with at all
+    pub solver_groups: CollisionGroups,    // which of those it also pushes apart
+    pub filter_pairs: bool,    // run PhysicsHooks::filter_pair for pairs involving this collider
+    pub modify_contacts: bool, // run PhysicsHooks::modify_contacts for its contacts
+    pub contact_force_threshold: Option<f32>, // report a ContactForceEvent when the total contact force exceeds this
 }
 
 impl Default for ColliderDef {
@@ -28,10 +56,13 @@ impl Default for ColliderDef {
             position: Vector3::zeros(),
             rotation: Quaternion::identity(),
             density: 1.0,
-            friction: 0.5,
-            restitution: 0.0,
+            material: MaterialId::DEFAULT,
             is_sensor: false,
-            collision_groups: 0xFFFFFFFF, // All groups by default
+            collision_groups: CollisionGroups::all(),
+            solver_groups: CollisionGroups::all(),
+            filter_pairs: false,
+            modify_contacts: false,
+            contact_force_threshold: None,
         }
     }
 }
\ No newline at end of file
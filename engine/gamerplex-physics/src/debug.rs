@@ -0,0 +1,430 @@
+// Renderer-agnostic debug drawing: World::debug_draw turns the simulation into
+// colored lines and points that the debug crate, an editor or a test can consume.
+
+use std::collections::VecDeque;
+
+use gamerplex_math::Vector3;
+use rapier3d::prelude::{
+    DebugRenderBackend,
+    DebugRenderMode,
+    DebugRenderObject,
+    DebugRenderPipeline,
+    DebugRenderStyle,
+    Point,
+    Real,
+};
+
+use crate::{EntityId, RaycastResult, World};
+
+// Linear RGBA
+pub type DebugColor = [f32; 4];
+
+pub const CONTACT_POINT_COLOR: DebugColor = [1.0, 0.2, 0.2, 1.0];
+pub const CONTACT_NORMAL_COLOR: DebugColor = [1.0, 0.8, 0.2, 1.0];
+pub const JOINT_ANCHOR_COLOR: DebugColor = [0.2, 0.6, 1.0, 1.0];
+pub const RAY_MISS_COLOR: DebugColor = [0.8, 0.8, 0.8, 1.0];
+pub const RAY_HIT_COLOR: DebugColor = [0.2, 1.0, 0.2, 1.0];
+
+// How many of the latest raycasts are kept for drawing
+pub const DEBUG_RAY_CAPACITY: usize = 64;
+// Rays with an unbounded max_distance are drawn this long
+const MAX_RAY_LENGTH: f32 = 1000.0;
+const NORMAL_LENGTH: f32 = 0.25;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DebugCategory {
+    Collider,
+    Aabb,
+    Contact,
+    Joint,
+    Raycast,
+}
+
+#[derive(Clone, Debug)]
+pub struct DebugLine {
+    pub start: Vector3,
+    pub end: Vector3,
+    pub color: DebugColor,
+    pub category: DebugCategory,
+    pub entity: Option<EntityId>, // body the line belongs to, the first body of a contact or joint
+    pub sleeping: bool,           // the body is asleep, its color is also dimmed
+}
+
+#[derive(Clone, Debug)]
+pub struct DebugPoint {
+    pub position: Vector3,
+    pub color: DebugColor,
+    pub category: DebugCategory,
+    pub entity: Option<EntityId>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DebugDrawing {
+    pub lines: Vec<DebugLine>,
+    pub points: Vec<DebugPoint>,
+}
+
+impl DebugDrawing {
+    pub fn lines_of(&self, category: DebugCategory) -> impl Iterator<Item = &DebugLine> {
+        self.lines.iter().filter(move |line| line.category == category)
+    }
+
+    pub fn points_of(&self, category: DebugCategory) -> impl Iterator<Item = &DebugPoint> {
+        self.points.iter().filter(move |point| point.category == category)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DebugDrawOptions {
+    pub colliders: bool, // wireframes of every collider shape
+    pub aabbs: bool,
+    pub contacts: bool,  // solver contact points and their normals
+    pub joints: bool,    // anchors and the separation between them
+}
+
+impl Default for DebugDrawOptions {
+    fn default() -> Self {
+        Self {
+            colliders: true,
+            aabbs: false,
+            contacts: true,
+            joints: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct DebugRay {
+    origin: Vector3,
+    end: Vector3,
+    hits: Vec<(Vector3, Vector3)>, // point and normal
+}
+
+// The latest DEBUG_RAY_CAPACITY raycasts, owned by the caller and filled only
+// for the rays it wants to see, queries never record anything by themselves
+#[derive(Clone, Debug, Default)]
+pub struct DebugRays {
+    rays: VecDeque<DebugRay>,
+}
+
+impl DebugRays {
+    // `hits` comes from raycast (as a slice) or raycast_all, the ray is drawn up
+    // to its farthest hit, misses are capped at MAX_RAY_LENGTH
+    pub fn record(&mut self, origin: Vector3, direction: Vector3, max_distance: f32, hits: &[RaycastResult]) {
+        let end = match hits.last() {
+            Some(hit) => hit.point,
+            None => ray_end(origin, direction, max_distance),
+        };
+        if self.rays.len() == DEBUG_RAY_CAPACITY {
+            self.rays.pop_front();
+        }
+        self.rays.push_back(DebugRay { origin, end, hits: hits.iter().map(|hit| (hit.point, hit.normal)).collect() });
+    }
+
+    pub fn clear(&mut self) {
+        self.rays.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.rays.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rays.is_empty()
+    }
+
+    fn draw(&self, drawing: &mut DebugDrawing) {
+        for ray in &self.rays {
+            let color = if ray.hits.is_empty() { RAY_MISS_COLOR } else { RAY_HIT_COLOR };
+            drawing.lines.push(debug_line(ray.origin, ray.end, color, DebugCategory::Raycast));
+
+            for (point, normal) in &ray.hits {
+                drawing.points.push(DebugPoint {
+                    position: *point,
+                    color: RAY_HIT_COLOR,
+                    category: DebugCategory::Raycast,
+                    entity: None,
+                });
+                drawing.lines.push(debug_line(*point, *point + *normal * NORMAL_LENGTH, RAY_HIT_COLOR, DebugCategory::Raycast));
+            }
+        }
+    }
+}
+
+impl World {
+    // `rays` are drawn on top when given, see DebugRays
+    pub fn debug_draw(&self, options: &DebugDrawOptions, rays: Option<&DebugRays>) -> DebugDrawing {
+        let mut mode = DebugRenderMode::empty();
+        mode.set(DebugRenderMode::COLLIDER_SHAPES, options.colliders);
+        mode.set(DebugRenderMode::COLLIDER_AABBS, options.aabbs);
+        mode.set(DebugRenderMode::IMPULSE_JOINTS, options.joints);
+
+        let mut backend = Collector { world: self, drawing: DebugDrawing::default() };
+        DebugRenderPipeline::new(DebugRenderStyle::default(), mode).render(
+            &mut backend,
+            &self.rigid_body_set,
+            &self.collider_set,
+            &self.impulse_joint_set,
+            &self.multibody_joint_set,
+            &self.narrow_phase,
+        );
+
+        let mut drawing = backend.drawing;
+        if options.contacts {
+            self.draw_contacts(&mut drawing);
+        }
+        if options.joints {
+            self.draw_joint_anchors(&mut drawing);
+        }
+        if let Some(rays) = rays {
+            rays.draw(&mut drawing);
+        }
+        drawing
+    }
+
+    fn draw_contacts(&self, drawing: &mut DebugDrawing) {
+        for pair in self.narrow_phase.contact_pairs().filter(|pair| pair.has_any_active_contact) {
+            let entity = self.collider_entity(pair.collider1);
+            let sleeping = self.collider_sleeping(pair.collider1) && self.collider_sleeping(pair.collider2);
+
+            for manifold in &pair.manifolds {
+                let normal = Vector3::from(manifold.data.normal);
+                for contact in &manifold.data.solver_contacts {
+                    let position = Vector3::from(contact.point);
+                    drawing.points.push(DebugPoint {
+                        position,
+                        color: CONTACT_POINT_COLOR,
+                        category: DebugCategory::Contact,
+                        entity,
+                    });
+                    drawing.lines.push(DebugLine {
+                        start: position,
+                        end: position + normal * NORMAL_LENGTH,
+                        color: CONTACT_NORMAL_COLOR,
+                        category: DebugCategory::Contact,
+                        entity,
+                        sleeping,
+                    });
+                }
+            }
+        }
+    }
+
+    fn draw_joint_anchors(&self, drawing: &mut DebugDrawing) {
+        for (_, joint) in self.impulse_joint_set.iter() {
+            for (body, frame) in [(joint.body1, joint.data.local_frame1), (joint.body2, joint.data.local_frame2)] {
+                let Some(rigid_body) = self.rigid_body_set.get(body) else {
+                    continue;
+                };
+                drawing.points.push(DebugPoint {
+                    position: Vector3::from((rigid_body.position() * frame).translation.vector),
+                    color: JOINT_ANCHOR_COLOR,
+                    category: DebugCategory::Joint,
+                    entity: self.body_entity_map.get(&body).copied(),
+                });
+            }
+        }
+    }
+
+    fn collider_sleeping(&self, collider: rapier3d::prelude::ColliderHandle) -> bool {
+        self.collider_set
+            .get(collider)
+            .and_then(|collider| collider.parent())
+            .is_some_and(|parent| self.rigid_body_set[parent].is_sleeping())
+    }
+}
+
+fn ray_end(origin: Vector3, direction: Vector3, max_distance: f32) -> Vector3 {
+    origin + direction.normalize() * max_distance.min(MAX_RAY_LENGTH)
+}
+
+fn debug_line(start: Vector3, end: Vector3, color: DebugColor, category: DebugCategory) -> DebugLine {
+    DebugLine { start, end, color, category, entity: None, sleeping: false }
+}
+
+// Collects what Rapier's debug pipeline draws, it covers every collider shape
+struct Collector<'a> {
+    world: &'a World,
+    drawing: DebugDrawing,
+}
+
+impl DebugRenderBackend for Collector<'_> {
+    fn draw_line(&mut self, object: DebugRenderObject, a: Point<Real>, b: Point<Real>, color: [f32; 4]) {
+        let (category, entity, sleeping) = match object {
+            DebugRenderObject::Collider(handle, _) => {
+                (DebugCategory::Collider, self.world.collider_entity(handle), self.world.collider_sleeping(handle))
+            }
+            DebugRenderObject::ColliderAabb(handle, _, _) => {
+                (DebugCategory::Aabb, self.world.collider_entity(handle), self.world.collider_sleeping(handle))
+            }
+            DebugRenderObject::ImpulseJoint(_, joint) => {
+                (DebugCategory::Joint, self.world.body_entity_map.get(&joint.body1).copied(), false)
+            }
+            _ => return,
+        };
+
+        self.drawing.lines.push(DebugLine {
+            start: a.into(),
+            end: b.into(),
+            color: hsla_to_rgba(color),
+            category,
+            entity,
+            sleeping,
+        });
+    }
+}
+
+// Rapier's debug style is in HSLA
+fn hsla_to_rgba([h, s, l, a]: [f32; 4]) -> DebugColor {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = (h / 60.0).rem_euclid(6.0);
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    [r + m, g + m, b + m, a]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, ColliderShape, CompoundPart, JointDef, JointType, QueryFilter};
+    use gamerplex_math::Quaternion;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn every_shape() -> Vec<ColliderShape> {
+        let cube = vec![
+            Vector3::new(-0.5, -0.5, -0.5), Vector3::new(0.5, -0.5, -0.5), Vector3::new(0.5, 0.5, -0.5), Vector3::new(-0.5, 0.5, -0.5),
+            Vector3::new(-0.5, -0.5, 0.5), Vector3::new(0.5, -0.5, 0.5), Vector3::new(0.5, 0.5, 0.5), Vector3::new(-0.5, 0.5, 0.5),
+        ];
+        let cube_indices = vec![
+            [0, 2, 1], [0, 3, 2], [4, 5, 6], [4, 6, 7], [0, 1, 5], [0, 5, 4],
+            [2, 3, 7], [2, 7, 6], [1, 2, 6], [1, 6, 5], [0, 4, 7], [0, 7, 3],
+        ];
+        vec![
+            ColliderShape::Sphere { radius: 0.5 },
+            ColliderShape::Box { half_extents: Vector3::new(0.5, 0.5, 0.5) },
+            ColliderShape::Capsule { height: 1.0, radius: 0.3 },
+            ColliderShape::Cylinder { height: 1.0, radius: 0.5 },
+            ColliderShape::ConvexHull { points: cube.clone() },
+            ColliderShape::Cone { height: 1.0, radius: 0.5 },
+            ColliderShape::Segment { a: Vector3::zeros(), b: Vector3::unit_y() },
+            ColliderShape::RoundBox { half_extents: Vector3::new(0.5, 0.5, 0.5), border_radius: 0.1 },
+            ColliderShape::RoundCylinder { height: 1.0, radius: 0.5, border_radius: 0.1 },
+            ColliderShape::RoundCone { height: 1.0, radius: 0.5, border_radius: 0.1 },
+            ColliderShape::TriMesh { vertices: cube, indices: cube_indices },
+            ColliderShape::HeightField { heights: vec![0.0; 9], rows: 3, columns: 3, scale: Vector3::ones() },
+            ColliderShape::Compound { parts: vec![CompoundPart {
+                shape: ColliderShape::Sphere { radius: 0.5 },
+                position: Vector3::unit_x(),
+                rotation: Quaternion::identity(),
+            }] },
+            // ConvexDecomposition ends up as a compound of hulls like the two above, and
+            // decomposing even a cube takes over a minute in an unoptimized build
+        ]
+    }
+
+    #[test]
+    fn wireframe_for_every_shape() {
+        let mut world = World::new(Vector3::zeros());
+        let shapes = every_shape();
+        for (entity, shape) in shapes.iter().enumerate() {
+            let body = world.add_rigid_body(entity as EntityId, &Body {
+                body_type: BodyType::Static,
+                position: Vector3::new(entity as f32 * 5.0, 0.0, 0.0),
+                ..Default::default()
+            });
+            world.add_collider(entity as EntityId, body, &ColliderDef { shape: shape.clone(), ..Default::default() }).unwrap();
+        }
+
+        let drawing = world.debug_draw(&DebugDrawOptions { aabbs: true, ..Default::default() }, None);
+        for entity in 0..shapes.len() as EntityId {
+            assert!(drawing.lines_of(DebugCategory::Collider).any(|line| line.entity == Some(entity)), "no wireframe for {:?}", shapes[entity as usize]);
+            // an AABB is 12 edges
+            assert_eq!(drawing.lines_of(DebugCategory::Aabb).filter(|line| line.entity == Some(entity)).count(), 12);
+        }
+    }
+
+    #[test]
+    fn contacts_sleep_and_joints() {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+        let ground = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, ground, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(10.0, 0.5, 10.0) },
+            ..Default::default()
+        }).unwrap();
+        let crate_body = world.add_rigid_body(1, &Body { position: Vector3::new(0.0, 1.0, 0.0), ..Default::default() });
+        world.add_collider(1, crate_body, &ColliderDef::default()).unwrap();
+
+        world.step(DT);
+        let drawing = world.debug_draw(&DebugDrawOptions::default(), None);
+        assert!(drawing.points_of(DebugCategory::Contact).count() > 0);
+        // normals point from the ground up into the crate
+        assert!(drawing.lines_of(DebugCategory::Contact).all(|line| line.end.y > line.start.y));
+        assert!(drawing.lines_of(DebugCategory::Collider).all(|line| !line.sleeping));
+
+        for _ in 0..300 {
+            world.step(DT);
+        }
+        let drawing = world.debug_draw(&DebugDrawOptions::default(), None);
+        assert!(drawing.lines_of(DebugCategory::Collider).filter(|line| line.entity == Some(1)).all(|line| line.sleeping));
+
+        let ball = world.add_rigid_body(2, &Body { position: Vector3::new(3.0, 2.0, 0.0), ..Default::default() });
+        world.add_collider(2, ball, &ColliderDef::default()).unwrap();
+        world.add_joint(1, 2, &JointDef {
+            joint_type: JointType::Spherical,
+            local_anchor_a: Vector3::new(0.0, 0.5, 0.0),
+            ..Default::default()
+        }).unwrap();
+
+        let drawing = world.debug_draw(&DebugDrawOptions::default(), None);
+        let anchors: Vec<_> = drawing.points_of(DebugCategory::Joint).collect();
+        assert_eq!(anchors.len(), 2);
+        assert_eq!(anchors[0].entity, Some(1));
+        assert!((anchors[1].position - Vector3::new(3.0, 2.0, 0.0)).length() < 1e-4);
+        assert!(drawing.lines_of(DebugCategory::Joint).count() > 0);
+    }
+
+    #[test]
+    fn recent_raycasts() {
+        let mut world = World::new(Vector3::zeros());
+        let wall = world.add_rigid_body(0, &Body { body_type: BodyType::Static, position: Vector3::new(5.0, 0.0, 0.0), ..Default::default() });
+        world.add_collider(0, wall, &ColliderDef::default()).unwrap();
+        world.update_query_pipeline();
+
+        let mut rays = DebugRays::default();
+        let hit = world.raycast(Vector3::zeros(), Vector3::unit_x(), 100.0, &QueryFilter::default());
+        assert!(hit.is_some());
+        rays.record(Vector3::zeros(), Vector3::unit_x(), 100.0, hit.as_slice());
+        let miss = world.raycast(Vector3::zeros(), -Vector3::unit_x(), f32::MAX, &QueryFilter::default());
+        assert!(miss.is_none());
+        rays.record(Vector3::zeros(), -Vector3::unit_x(), f32::MAX, miss.as_slice());
+
+        // nothing is drawn unless the rays are handed over
+        assert_eq!(world.debug_draw(&DebugDrawOptions::default(), None).lines_of(DebugCategory::Raycast).count(), 0);
+
+        let drawing = world.debug_draw(&DebugDrawOptions::default(), Some(&rays));
+        let lines: Vec<_> = drawing.lines_of(DebugCategory::Raycast).collect();
+        // the hit ray stops at the wall and draws the normal, the miss ray is capped
+        assert_eq!(lines.len(), 3);
+        assert!((lines[0].end - Vector3::new(4.5, 0.0, 0.0)).length() < 1e-4);
+        assert_eq!(lines[0].color, RAY_HIT_COLOR);
+        assert_eq!(lines[2].end, Vector3::new(-MAX_RAY_LENGTH, 0.0, 0.0));
+        assert_eq!(drawing.points_of(DebugCategory::Raycast).count(), 1);
+
+        for _ in 0..DEBUG_RAY_CAPACITY {
+            rays.record(Vector3::zeros(), Vector3::unit_y(), 1.0, &[]);
+        }
+        assert_eq!(rays.len(), DEBUG_RAY_CAPACITY);
+
+        rays.clear();
+        assert!(rays.is_empty());
+    }
+}
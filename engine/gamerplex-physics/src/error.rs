@@ -0,0 +1,53 @@
+use std::fmt;
+
+use crate::handles::{BodyHandle, ColliderHandle, EntityId, JointHandle};
+use crate::material::MaterialId;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PhysicsError {
+    NoRigidBody(EntityId),    // entity has no body registered in the world
+    BodyNotDynamic(EntityId), // operation only makes sense on dynamic bodies
+    InvalidBodyHandle(BodyHandle), // handle is invalid or its body has been removed
+    InvalidColliderHandle(ColliderHandle),
+    InvalidJointHandle(JointHandle),
+    BodyNotKinematic(EntityId),
+    NoCapsuleCollider(EntityId),
+    NoCharacterController(EntityId),
+    NoSensorCollider(EntityId),
+    NoVehicle(BodyHandle),    // no vehicle was added on this chassis
+    InvalidWheel(usize),
+    ThreadPool(String),       // the thread pool could not be created
+    InvalidShape(String),     // the collider shape could not be built
+    InvalidSnapshot(String),  // snapshot bytes could not be encoded or decoded
+    InvalidScene(String),     // scene text could not be parsed or written, or describes an impossible world
+    TooManyLayers,            // all 32 collision layers are taken
+    UnknownLayer(String),
+    UnknownMaterial(MaterialId), // id was not returned by register_material
+}
+
+impl fmt::Display for PhysicsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhysicsError::NoRigidBody(entity) => write!(f, "entity {} has no rigid body", entity),
+            PhysicsError::BodyNotDynamic(entity) => write!(f, "rigid body of entity {} is not dynamic", entity),
+            PhysicsError::InvalidBodyHandle(handle) => write!(f, "body handle {:?} does not refer to a live rigid body", handle),
+            PhysicsError::InvalidColliderHandle(handle) => write!(f, "collider handle {:?} does not refer to a live collider", handle),
+            PhysicsError::InvalidJointHandle(handle) => write!(f, "joint handle {:?} does not refer to a live joint", handle),
+            PhysicsError::BodyNotKinematic(entity) => write!(f, "rigid body of entity {} is not kinematic", entity),
+            PhysicsError::NoCapsuleCollider(entity) => write!(f, "entity {} has no capsule collider", entity),
+            PhysicsError::NoCharacterController(entity) => write!(f, "entity {} has no character controller", entity),
+            PhysicsError::NoSensorCollider(entity) => write!(f, "entity {} has no sensor collider", entity),
+            PhysicsError::NoVehicle(handle) => write!(f, "body handle {:?} is not the chassis of a vehicle", handle),
+            PhysicsError::InvalidWheel(index) => write!(f, "vehicle has no wheel {}", index),
+            PhysicsError::ThreadPool(reason) => write!(f, "could not create physics thread pool: {}", reason),
+            PhysicsError::InvalidShape(reason) => write!(f, "invalid collider shape: {}", reason),
+            PhysicsError::InvalidSnapshot(reason) => write!(f, "invalid physics snapshot: {}", reason),
+            PhysicsError::InvalidScene(reason) => write!(f, "invalid physics scene: {}", reason),
+            PhysicsError::TooManyLayers => write!(f, "all 32 collision layers are already defined"),
+            PhysicsError::UnknownLayer(name) => write!(f, "collision layer {:?} is not defined", name),
+            PhysicsError::UnknownMaterial(id) => write!(f, "physics material {} is not registered", id.index()),
+        }
+    }
+}
+
+impl std::error::Error for PhysicsError {}
@@ -1,6 +1,13 @@
 use gamerplex_math::Vector3;
+use rapier3d::prelude::{
+    ActiveEvents,
+    ColliderHandle as RapierColliderHandle,
+    CollisionEvent as RapierCollisionEvent,
+    ContactPair,
+};
+use serde::{Deserialize, Serialize};
 
-use crate::{handles::EntityId, world::World};
+use crate::{handles::{ColliderHandle, EntityId}, error::PhysicsError, world::World};
 
 #[derive(Clone, Debug)]
 pub enum CollisionEventType {
@@ -16,7 +23,82 @@ pub struct CollisionEvent {
     pub event_type: CollisionEventType,
     pub contact_point: Vector3,  // World space contact point
     pub normal: Vector3,         // Contact normal (direction)
-    pub impulse: f32,            // Collision impulse magnitude
+    pub impulse: f32,            // Sum of the contact impulses applied during the last substep
+    pub is_sensor: bool,         // At least one of the colliders is a sensor
+    pub surface_a: u32,          // Surface tag of entity_a's material
+    pub surface_b: u32,          // Surface tag of entity_b's material
+}
+
+// A pair whose contact force exceeded the contact_force_threshold of one of its colliders
+// during a substep, e.g. to break props, apply fall damage or shake the camera
+#[derive(Clone, Debug)]
+pub struct ContactForceEvent {
+    pub entity_a: EntityId,
+    pub entity_b: EntityId,
+    pub total_force: Vector3,         // Sum of all contact forces, applied to entity_b
+    pub total_force_magnitude: f32,   // Sum of the magnitudes, this is what the threshold is compared to
+    pub max_force_direction: Vector3, // Normal of the manifold with the largest force, pointing from a to b
+    pub max_force_magnitude: f32,
+    pub contact_points: Vec<Vector3>, // World space points the solver pushed on
+}
+
+// Forces one contact pair exerted during a substep, shared by collision and force events
+struct ContactForces {
+    total_force: Vector3,
+    total_force_magnitude: f32,
+    max_force_direction: Vector3,
+    max_force_magnitude: f32,
+    contact_points: Vec<Vector3>,
+}
+
+fn contact_forces(pair: &ContactPair, dt: f32) -> ContactForces {
+    let mut forces = ContactForces {
+        total_force: Vector3::zeros(),
+        total_force_magnitude: 0.0,
+        max_force_direction: Vector3::zeros(),
+        max_force_magnitude: 0.0,
+        contact_points: Vec::new(),
+    };
+
+    for manifold in &pair.manifolds {
+        let manifold_impulse: f32 = manifold.points.iter().map(|point| point.data.impulse).sum();
+        let normal = Vector3::from(manifold.data.normal);
+
+        if manifold_impulse > forces.max_force_magnitude {
+            forces.max_force_magnitude = manifold_impulse;
+            forces.max_force_direction = normal;
+        }
+        forces.total_force = forces.total_force + normal * manifold_impulse;
+        forces.total_force_magnitude += manifold_impulse;
+        forces.contact_points.extend(manifold.data.solver_contacts.iter().map(|contact| Vector3::from(contact.point)));
+    }
+
+    // impulses were accumulated over the substep, turn them into forces
+    let inv_dt = if dt > 0.0 { 1.0 / dt } else { 0.0 };
+    forces.total_force = forces.total_force * inv_dt;
+    forces.total_force_magnitude *= inv_dt;
+    forces.max_force_magnitude *= inv_dt;
+    forces
+}
+
+// A pair of colliders that is currently touching
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ActiveContact {
+    pub entity_a: EntityId,
+    pub entity_b: EntityId,
+    pub is_sensor: bool,
+    pub(crate) reported: bool, // false until the pair has been through a full step()
+}
+
+pub(crate) type ContactKey = (RapierColliderHandle, RapierColliderHandle);
+
+// Rapier does not guarantee the order of the two colliders between events
+pub(crate) fn contact_key(a: RapierColliderHandle, b: RapierColliderHandle) -> ContactKey {
+    if a.into_raw_parts() <= b.into_raw_parts() {
+        (a, b)
+    } else {
+        (b, a)
+    }
 }
 
 impl World {
@@ -24,8 +106,388 @@ impl World {
     pub fn collision_events(&self) -> &[CollisionEvent] {
         &self.collision_events
     }
-    
+
+    pub fn contact_force_events(&self) -> &[ContactForceEvent] {
+        &self.contact_force_events
+    }
+
+    // None stops force events for this collider, Some(0.0) reports every solid contact
+    pub fn set_contact_force_threshold(&mut self, handle: ColliderHandle, threshold: Option<f32>) -> Result<(), PhysicsError> {
+        let collider = self.collider_set
+            .get_mut(handle.to_rapier_handle())
+            .ok_or(PhysicsError::InvalidColliderHandle(handle))?;
+
+        let mut events = collider.active_events();
+        events.set(ActiveEvents::CONTACT_FORCE_EVENTS, threshold.is_some());
+        collider.set_active_events(events);
+        collider.set_contact_force_event_threshold(threshold.unwrap_or(0.0));
+        Ok(())
+    }
+
     pub fn clear_events(&mut self) {
         self.collision_events.clear();
+        self.contact_force_events.clear();
+        self.trigger_events.clear();
+        self.joint_broken_events.clear();
+        self.sleep_events.clear();
+    }
+
+    pub fn active_contacts(&self) -> impl Iterator<Item = &ActiveContact> {
+        self.active_contacts.values()
+    }
+
+    // Solid contacts only, sensor overlaps are not "touching"
+    pub fn is_touching(&self, a: EntityId, b: EntityId) -> bool {
+        self.active_contacts.values().any(|contact| {
+            !contact.is_sensor
+                && ((contact.entity_a == a && contact.entity_b == b)
+                    || (contact.entity_a == b && contact.entity_b == a))
+        })
+    }
+
+    pub fn touching_entities(&self, entity: EntityId) -> Vec<EntityId> {
+        let mut entities: Vec<EntityId> = self.active_contacts.values()
+            .filter(|contact| !contact.is_sensor)
+            .filter_map(|contact| {
+                if contact.entity_a == entity {
+                    Some(contact.entity_b)
+                } else if contact.entity_b == entity {
+                    Some(contact.entity_a)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        entities.sort_unstable();
+        entities.dedup();
+        entities
+    }
+
+    // Events accumulate in `collision_events` until `clear_events` is called
+    pub(crate) fn process_collision_events(&mut self) {
+        while let Ok(event) = self.collision_recv.try_recv() {
+            let (collider1, collider2) = (event.collider1(), event.collider2());
+            let key = contact_key(collider1, collider2);
+
+            if event.sensor() {
+                self.track_trigger_overlap(collider1, collider2, event.started());
+            }
+
+            match event {
+                RapierCollisionEvent::Started(..) => {
+                    let Some(collision) = self.build_collision_event(
+                        collider1,
+                        collider2,
+                        CollisionEventType::Started,
+                        event.sensor(),
+                    ) else {
+                        continue;
+                    };
+
+                    self.active_contacts.insert(key, ActiveContact {
+                        entity_a: collision.entity_a,
+                        entity_b: collision.entity_b,
+                        is_sensor: collision.is_sensor,
+                        reported: false,
+                    });
+                    self.collision_events.push(collision);
+                }
+                RapierCollisionEvent::Stopped(..) => {
+                    let tracked = self.active_contacts.remove(&key);
+                    let collision = self
+                        .build_collision_event(collider1, collider2, CollisionEventType::Ended, event.sensor())
+                        // a removed collider can no longer be resolved, fall back to the tracked pair
+                        .or_else(|| tracked.map(|contact| CollisionEvent {
+                            entity_a: contact.entity_a,
+                            entity_b: contact.entity_b,
+                            event_type: CollisionEventType::Ended,
+                            contact_point: Vector3::zeros(),
+                            normal: Vector3::zeros(),
+                            impulse: 0.0,
+                            is_sensor: contact.is_sensor,
+                            surface_a: 0,
+                            surface_b: 0,
+                        }));
+
+                    if let Some(collision) = collision {
+                        self.collision_events.push(collision);
+                    }
+                }
+            }
+        }
+
+        let dt = self.integration_parameters.dt;
+        while let Ok(event) = self.contact_force_recv.try_recv() {
+            // rapier only says the threshold was crossed, the details come from the narrow phase
+            let Some(pair) = self.narrow_phase.contact_pair(event.collider1, event.collider2) else {
+                continue;
+            };
+            let (Some(entity_a), Some(entity_b)) = (self.collider_entity(pair.collider1), self.collider_entity(pair.collider2)) else {
+                continue;
+            };
+
+            let forces = contact_forces(pair, dt);
+            self.contact_force_events.push(ContactForceEvent {
+                entity_a,
+                entity_b,
+                total_force: forces.total_force,
+                total_force_magnitude: forces.total_force_magnitude,
+                max_force_direction: forces.max_force_direction,
+                max_force_magnitude: forces.max_force_magnitude,
+                contact_points: forces.contact_points,
+            });
+        }
+    }
+
+    // Called once per step() so pairs that stay in contact over several substeps are reported once
+    pub(crate) fn emit_ongoing_events(&mut self) {
+        let mut ongoing = Vec::new();
+
+        for ((collider1, collider2), contact) in self.active_contacts.iter_mut() {
+            if !contact.reported {
+                // the Started event already covers this step
+                contact.reported = true;
+                continue;
+            }
+            let order = (contact.entity_a, contact.entity_b, collider1.into_raw_parts(), collider2.into_raw_parts());
+            ongoing.push((order, *collider1, *collider2, contact.is_sensor));
+        }
+        // the hash map iterates in a different order every run
+        ongoing.sort_unstable_by_key(|(order, ..)| *order);
+
+        for (_, collider1, collider2, is_sensor) in ongoing {
+            if let Some(collision) = self.build_collision_event(collider1, collider2, CollisionEventType::Ongoing, is_sensor) {
+                self.collision_events.push(collision);
+            }
+        }
     }
-}
\ No newline at end of file
+
+    pub(crate) fn collider_entity(&self, collider: RapierColliderHandle) -> Option<EntityId> {
+        let parent = self.collider_set.get(collider)?.parent()?;
+        self.body_entity_map.get(&parent).copied()
+    }
+
+    fn build_collision_event(
+        &self,
+        collider1: RapierColliderHandle,
+        collider2: RapierColliderHandle,
+        event_type: CollisionEventType,
+        is_sensor: bool,
+    ) -> Option<CollisionEvent> {
+        let mut event = CollisionEvent {
+            entity_a: self.collider_entity(collider1)?,
+            entity_b: self.collider_entity(collider2)?,
+            event_type,
+            contact_point: Vector3::zeros(),
+            normal: Vector3::zeros(),
+            impulse: 0.0,
+            is_sensor,
+            surface_a: self.collider_surface(collider1),
+            surface_b: self.collider_surface(collider2),
+        };
+
+        // sensors and separated pairs have no contact data, the zeroed fields stay
+        let Some(pair) = self.narrow_phase.contact_pair(collider1, collider2) else {
+            return Some(event);
+        };
+
+        // the narrow phase may store the pair in the opposite order, keep the normal pointing from a to b
+        if pair.collider1 != collider1 {
+            std::mem::swap(&mut event.entity_a, &mut event.entity_b);
+            std::mem::swap(&mut event.surface_a, &mut event.surface_b);
+        }
+
+        if let Some((manifold, contact)) = pair.find_deepest_contact() {
+            if let Some(collider) = self.collider_set.get(pair.collider1) {
+                let point = collider.position() * contact.local_p1;
+                event.contact_point = Vector3::from(point);
+            }
+            event.normal = Vector3::from(manifold.data.normal);
+        }
+        event.impulse = contact_forces(pair, self.integration_parameters.dt).total_force_magnitude
+            * self.integration_parameters.dt;
+
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, ColliderShape};
+
+    fn drop_box(height: f32, threshold: Option<f32>) -> World {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+
+        let ground = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, ground, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(10.0, 0.5, 10.0) },
+            ..Default::default()
+        }).unwrap();
+
+        let body = world.add_rigid_body(1, &Body { position: Vector3::new(0.0, height, 0.0), ..Default::default() });
+        world.add_collider(1, body, &ColliderDef { contact_force_threshold: threshold, ..Default::default() }).unwrap();
+        world
+    }
+
+    fn event_types(world: &World) -> Vec<&'static str> {
+        world.collision_events()
+            .iter()
+            .filter(|event| (event.entity_a, event.entity_b) == (0, 1) || (event.entity_a, event.entity_b) == (1, 0))
+            .map(|event| match event.event_type {
+                CollisionEventType::Started => "started",
+                CollisionEventType::Ongoing => "ongoing",
+                CollisionEventType::Ended => "ended",
+            })
+            .collect()
+    }
+
+    // Steps until the box rests on the ground
+    fn land(world: &mut World) {
+        for _ in 0..60 {
+            world.step(1.0 / 60.0);
+            if world.is_touching(0, 1) {
+                return;
+            }
+        }
+        panic!("the box never landed");
+    }
+
+    #[test]
+    fn landing_and_leaving_the_ground() {
+        let mut world = drop_box(1.5, None);
+        assert!(!world.is_touching(0, 1));
+
+        land(&mut world);
+        assert_eq!(event_types(&world), vec!["started"]);
+        let started = &world.collision_events()[0];
+        assert!(!started.is_sensor);
+        assert!(started.normal.y.abs() > 0.99);
+        assert!(world.is_touching(1, 0));
+        assert_eq!(world.touching_entities(1), vec![0]);
+        assert_eq!(world.touching_entities(0), vec![1]);
+
+        world.clear_events();
+        world.set_linear_velocity(1, Vector3::new(0.0, 10.0, 0.0)).unwrap();
+        for _ in 0..10 {
+            world.step(1.0 / 60.0);
+        }
+        assert_eq!(event_types(&world).last(), Some(&"ended"));
+        assert!(!world.is_touching(0, 1));
+        assert!(world.touching_entities(1).is_empty());
+        assert_eq!(world.active_contacts().count(), 0);
+    }
+
+    #[test]
+    fn removed_colliders_still_end_their_contacts() {
+        let mut world = drop_box(1.5, None);
+        land(&mut world);
+        world.clear_events();
+
+        world.remove_collider(world.colliders(1)[0]).unwrap();
+        world.step(1.0 / 60.0);
+        assert_eq!(event_types(&world), vec!["ended"]);
+        assert!(!world.is_touching(0, 1));
+        assert_eq!(world.active_contacts().count(), 0);
+    }
+
+    #[test]
+    fn resting_pairs_report_ongoing_once_per_step() {
+        let mut world = drop_box(1.5, None);
+        land(&mut world);
+
+        world.clear_events();
+        world.step(1.0 / 60.0);
+        assert_eq!(event_types(&world), vec!["ongoing"]);
+
+        // three substeps in one call still make a single event
+        world.clear_events();
+        world.step(3.5 / 60.0);
+        assert_eq!(world.step_stats().substeps, 3);
+        assert_eq!(event_types(&world), vec!["ongoing"]);
+        assert!(world.collision_events()[0].impulse > 0.0);
+    }
+
+    #[test]
+    fn sensor_contacts_are_flagged() {
+        let mut world = drop_box(1.5, None);
+        let zone = world.add_rigid_body(2, &Body { body_type: BodyType::Static, position: Vector3::new(0.0, 1.0, 0.0), ..Default::default() });
+        world.add_collider(2, zone, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(2.0, 2.0, 2.0) },
+            is_sensor: true,
+            ..Default::default()
+        }).unwrap();
+
+        land(&mut world);
+        world.step(1.0 / 60.0);
+        let is_sensor = |other: EntityId| world.collision_events()
+            .iter()
+            .filter(|event| event.entity_a == other || event.entity_b == other)
+            .all(|event| event.is_sensor == (other == 2));
+
+        assert!(world.collision_events().iter().any(|event| event.entity_a == 2 || event.entity_b == 2));
+        assert!(is_sensor(2));
+        assert!(is_sensor(0));
+        // overlapping a sensor is not touching it
+        assert!(!world.is_touching(1, 2));
+        assert_eq!(world.touching_entities(1), vec![0]);
+    }
+
+    #[test]
+    fn ongoing_events_come_out_in_entity_order() {
+        let mut world = drop_box(1.0, None);
+        for (i, entity) in [7, 2, 9, 4, 8, 3, 6, 5].into_iter().enumerate() {
+            let body = world.add_rigid_body(entity, &Body { position: Vector3::new(i as f32 * 2.0 - 8.0, 1.0, 5.0), ..Default::default() });
+            world.add_collider(entity, body, &ColliderDef::default()).unwrap();
+        }
+        for _ in 0..10 {
+            world.step(1.0 / 60.0);
+        }
+
+        world.clear_events();
+        world.step(1.0 / 60.0);
+        let pairs: Vec<_> = world.collision_events().iter().map(|event| (event.entity_a, event.entity_b)).collect();
+        let mut sorted = pairs.clone();
+        sorted.sort_unstable();
+        assert_eq!(pairs.len(), 9);
+        assert_eq!(pairs, sorted);
+    }
+
+    #[test]
+    fn hard_landings_report_contact_forces() {
+        let mut world = drop_box(5.0, Some(100.0));
+        let mut impacts = Vec::new();
+
+        for _ in 0..120 {
+            world.step(1.0 / 60.0);
+            if let Some(impact) = world.contact_force_events().first() {
+                // the collision event of the same step is built from the same contacts
+                let collision = world.collision_events().last().unwrap();
+                assert!((collision.impulse - impact.total_force_magnitude / 60.0).abs() < 1e-3);
+                impacts.push(impact.clone());
+            }
+            world.clear_events();
+        }
+
+        // only the landing is above the threshold, resting weight is about 10 N
+        let impact = impacts.first().expect("the landing should exceed the threshold");
+        assert!(impact.total_force_magnitude > 100.0);
+        assert!(!impact.contact_points.is_empty());
+        assert!(impact.max_force_direction.y.abs() > 0.99);
+        assert!(impacts.len() < 5, "{} force events", impacts.len());
+    }
+
+    #[test]
+    fn colliders_without_threshold_stay_quiet() {
+        let mut world = drop_box(5.0, None);
+        for _ in 0..120 {
+            world.step(1.0 / 60.0);
+            assert!(world.contact_force_events().is_empty());
+        }
+
+        let collider = world.colliders(1)[0];
+        world.set_contact_force_threshold(collider, Some(0.0)).unwrap();
+        world.step(1.0 / 60.0);
+        assert!(!world.contact_force_events().is_empty());
+    }
+}
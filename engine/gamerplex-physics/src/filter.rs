@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+
+use gamerplex_math::Vector3;
+use rapier3d::prelude::{
+    ContactModificationContext,
+    PairFilterContext,
+    PhysicsHooks as RapierPhysicsHooks,
+    RigidBodyHandle,
+    SolverFlags,
+    Vector,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::integration::rapier;
+use crate::{ColliderHandle, EntityId, PhysicsError, World};
+
+// Two colliders interact when each one is a member of a group the other one filters for
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollisionGroups {
+    pub memberships: u32, // groups this collider belongs to
+    pub filter: u32,      // groups this collider interacts with
+}
+
+impl CollisionGroups {
+    pub fn new(memberships: u32, filter: u32) -> Self {
+        Self { memberships, filter }
+    }
+
+    pub fn all() -> Self {
+        Self::new(u32::MAX, u32::MAX)
+    }
+
+    pub fn none() -> Self {
+        Self::new(0, 0)
+    }
+
+    pub fn interacts_with(&self, other: &CollisionGroups) -> bool {
+        self.memberships & other.filter != 0 && other.memberships & self.filter != 0
+    }
+}
+
+impl Default for CollisionGroups {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+// Names for the 32 group bits, so gameplay code can say "player_bullet" instead of 1 << 3
+#[derive(Clone, Debug, Default)]
+pub struct CollisionLayers {
+    names: Vec<String>,
+}
+
+impl CollisionLayers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns the layer's bit, defining an existing name again returns the same bit
+    pub fn define(&mut self, name: &str) -> Result<u32, PhysicsError> {
+        if let Ok(bit) = self.layer(name) {
+            return Ok(bit);
+        }
+        if self.names.len() == u32::BITS as usize {
+            return Err(PhysicsError::TooManyLayers);
+        }
+
+        self.names.push(name.to_string());
+        Ok(1 << (self.names.len() - 1))
+    }
+
+    pub fn layer(&self, name: &str) -> Result<u32, PhysicsError> {
+        self.names
+            .iter()
+            .position(|layer| layer == name)
+            .map(|index| 1 << index)
+            .ok_or_else(|| PhysicsError::UnknownLayer(name.to_string()))
+    }
+
+    pub fn mask(&self, names: &[&str]) -> Result<u32, PhysicsError> {
+        names.iter().try_fold(0, |mask, name| Ok(mask | self.layer(name)?))
+    }
+
+    // e.g. groups(&["player_bullet"], &["enemy", "world"])
+    pub fn groups(&self, member_of: &[&str], interacts_with: &[&str]) -> Result<CollisionGroups, PhysicsError> {
+        Ok(CollisionGroups::new(self.mask(member_of)?, self.mask(interacts_with)?))
+    }
+}
+
+// User callbacks run by the solver, only for colliders that opted in through
+// ColliderDef::filter_pairs and ColliderDef::modify_contacts. They run in the
+// middle of the step, so they cannot touch the World.
+pub trait PhysicsHooks: Send + Sync {
+    // Return false to let the two entities pass through each other
+    fn filter_pair(&self, _entity_a: EntityId, _entity_b: EntityId) -> bool {
+        true
+    }
+
+    // Called for every touching pair each step, e.g. for one-way platforms or conveyor belts
+    fn modify_contacts(&self, _contacts: &mut ContactModification) {}
+}
+
+// The contacts between two entities as the solver is about to resolve them
+pub struct ContactModification<'a, 'b> {
+    pub entity_a: EntityId,
+    pub entity_b: EntityId,
+    context: &'a mut ContactModificationContext<'b>,
+}
+
+impl ContactModification<'_, '_> {
+    // World space, pointing from entity_a to entity_b
+    pub fn normal(&self) -> Vector3 {
+        Vector3::from(*self.context.normal)
+    }
+
+    pub fn contact_count(&self) -> usize {
+        self.context.solver_contacts.len()
+    }
+
+    // The entities pass through each other this step
+    pub fn clear_contacts(&mut self) {
+        self.context.solver_contacts.clear();
+    }
+
+    pub fn set_friction(&mut self, friction: f32) {
+        for contact in self.context.solver_contacts.iter_mut() {
+            contact.friction = friction;
+        }
+    }
+
+    pub fn set_restitution(&mut self, restitution: f32) {
+        for contact in self.context.solver_contacts.iter_mut() {
+            contact.restitution = restitution;
+        }
+    }
+
+    // World space surface velocity, e.g. a conveyor belt that carries whatever lies on it
+    pub fn set_tangent_velocity(&mut self, velocity: Vector3) {
+        let velocity = Vector::from(velocity);
+        for contact in self.context.solver_contacts.iter_mut() {
+            contact.tangent_velocity = velocity;
+        }
+    }
+
+    // Only lets things land on `platform` from the `up` side (world space), anything
+    // coming from below passes through until it is fully out of the platform
+    pub fn one_way_platform(&mut self, platform: EntityId, up: Vector3, allowed_angle: f32) {
+        let up = Vector::from(up);
+        // rapier expects the allowed normal in the local space of the first collider
+        let normal = if platform == self.entity_a { up } else { -up };
+        let rotation = self.context.colliders[self.context.collider1].position().rotation;
+
+        self.context.update_as_oneway_platform(&rotation.inverse_transform_vector(&normal), allowed_angle);
+    }
+}
+
+// Translates rapier's collider handles into entities before calling the user hooks
+pub(crate) struct HookBridge<'a> {
+    pub hooks: Option<&'a dyn PhysicsHooks>,
+    pub body_entity_map: &'a HashMap<RigidBodyHandle, EntityId>,
+}
+
+impl HookBridge<'_> {
+    fn entities(&self, body1: Option<RigidBodyHandle>, body2: Option<RigidBodyHandle>) -> Option<(EntityId, EntityId)> {
+        let entity_a = self.body_entity_map.get(&body1?)?;
+        let entity_b = self.body_entity_map.get(&body2?)?;
+        Some((*entity_a, *entity_b))
+    }
+
+    fn filter_pair(&self, context: &PairFilterContext) -> bool {
+        match (self.hooks, self.entities(context.rigid_body1, context.rigid_body2)) {
+            (Some(hooks), Some((entity_a, entity_b))) => hooks.filter_pair(entity_a, entity_b),
+            _ => true,
+        }
+    }
+}
+
+impl RapierPhysicsHooks for HookBridge<'_> {
+    fn filter_contact_pair(&self, context: &PairFilterContext) -> Option<SolverFlags> {
+        self.filter_pair(context).then_some(SolverFlags::COMPUTE_IMPULSES)
+    }
+
+    fn filter_intersection_pair(&self, context: &PairFilterContext) -> bool {
+        self.filter_pair(context)
+    }
+
+    fn modify_solver_contacts(&self, context: &mut ContactModificationContext) {
+        let (Some(hooks), Some((entity_a, entity_b))) = (self.hooks, self.entities(context.rigid_body1, context.rigid_body2)) else {
+            return;
+        };
+
+        hooks.modify_contacts(&mut ContactModification { entity_a, entity_b, context });
+    }
+}
+
+impl World {
+    // Replaces the hooks set before, pass None to remove them
+    pub fn set_physics_hooks(&mut self, hooks: Option<Box<dyn PhysicsHooks>>) {
+        self.physics_hooks = hooks;
+    }
+
+    pub fn set_collision_groups(&mut self, handle: ColliderHandle, groups: CollisionGroups) -> Result<(), PhysicsError> {
+        let collider = self.collider_set
+            .get_mut(handle.to_rapier_handle())
+            .ok_or(PhysicsError::InvalidColliderHandle(handle))?;
+        collider.set_collision_groups(rapier::to_interaction_groups(&groups));
+        Ok(())
+    }
+
+    // Solver groups only decide whether contacts push the colliders apart,
+    // the pair still generates collision events
+    pub fn set_solver_groups(&mut self, handle: ColliderHandle, groups: CollisionGroups) -> Result<(), PhysicsError> {
+        let collider = self.collider_set
+            .get_mut(handle.to_rapier_handle())
+            .ok_or(PhysicsError::InvalidColliderHandle(handle))?;
+        collider.set_solver_groups(rapier::to_interaction_groups(&groups));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, ColliderShape, CollisionEventType};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    // Static slab 0 with its top at y = 0.1 and a unit box 1 at the given height
+    fn slab_and_box(slab: ColliderDef, box_def: ColliderDef, height: f32) -> World {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+        let ground = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, ground, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(5.0, 0.1, 5.0) },
+            ..slab
+        }).unwrap();
+
+        let body = world.add_rigid_body(1, &Body { position: Vector3::new(0.0, height, 0.0), ..Default::default() });
+        world.add_collider(1, body, &box_def).unwrap();
+        world
+    }
+
+    fn height(world: &World) -> f32 {
+        world.rigid_body(1).unwrap().translation().y
+    }
+
+    struct PassThrough;
+
+    impl PhysicsHooks for PassThrough {
+        fn filter_pair(&self, entity_a: EntityId, entity_b: EntityId) -> bool {
+            entity_a.min(entity_b) != 0 || entity_a.max(entity_b) != 1
+        }
+    }
+
+    struct OneWay;
+
+    impl PhysicsHooks for OneWay {
+        fn modify_contacts(&self, contacts: &mut ContactModification) {
+            contacts.one_way_platform(0, Vector3::unit_y(), 0.5);
+        }
+    }
+
+    #[test]
+    fn layers_build_groups() {
+        let mut layers = CollisionLayers::new();
+        let player = layers.define("player").unwrap();
+        let enemy = layers.define("enemy").unwrap();
+        let bullet = layers.define("player_bullet").unwrap();
+        assert_eq!(layers.define("enemy").unwrap(), enemy);
+
+        let bullet_groups = layers.groups(&["player_bullet"], &["enemy"]).unwrap();
+        let enemy_groups = layers.groups(&["enemy"], &["player", "player_bullet"]).unwrap();
+        let player_groups = layers.groups(&["player"], &["enemy"]).unwrap();
+
+        assert_eq!(bullet_groups, CollisionGroups::new(bullet, enemy));
+        assert!(bullet_groups.interacts_with(&enemy_groups));
+        assert!(!bullet_groups.interacts_with(&player_groups));
+        assert_eq!(layers.layer("wall"), Err(PhysicsError::UnknownLayer("wall".to_string())));
+        assert_ne!(player, enemy);
+    }
+
+    #[test]
+    fn layers_run_out_after_32() {
+        let mut layers = CollisionLayers::new();
+        for i in 0..32 {
+            layers.define(&format!("layer{}", i)).unwrap();
+        }
+        assert_eq!(layers.define("one_more"), Err(PhysicsError::TooManyLayers));
+    }
+
+    #[test]
+    fn pair_filters_let_entities_pass() {
+        let mut world = slab_and_box(ColliderDef::default(), ColliderDef { filter_pairs: true, ..Default::default() }, 1.0);
+        world.set_physics_hooks(Some(Box::new(PassThrough)));
+        for _ in 0..60 {
+            world.step(DT);
+        }
+        assert!(height(&world) < -1.0);
+        assert!(world.collision_events().is_empty());
+
+        // without the hooks the box lands
+        let mut world = slab_and_box(ColliderDef::default(), ColliderDef { filter_pairs: true, ..Default::default() }, 1.0);
+        for _ in 0..60 {
+            world.step(DT);
+        }
+        assert!((height(&world) - 0.6).abs() < 0.05);
+    }
+
+    #[test]
+    fn one_way_platforms_are_passed_from_below() {
+        let slab = ColliderDef { modify_contacts: true, ..Default::default() };
+        let mut world = slab_and_box(slab, ColliderDef::default(), -1.0);
+        world.set_physics_hooks(Some(Box::new(OneWay)));
+        world.set_linear_velocity(1, Vector3::new(0.0, 8.0, 0.0)).unwrap();
+
+        let mut highest = f32::MIN;
+        for _ in 0..180 {
+            world.step(DT);
+            highest = highest.max(height(&world));
+        }
+        // jumped through the platform, then landed on top of it
+        assert!(highest > 1.5);
+        assert!((height(&world) - 0.6).abs() < 0.05);
+        assert!(world.is_touching(0, 1));
+    }
+
+    #[test]
+    fn solver_groups_keep_events_without_a_response() {
+        let mut world = slab_and_box(ColliderDef::default(), ColliderDef::default(), 1.0);
+        world.set_solver_groups(world.colliders(1)[0], CollisionGroups::none()).unwrap();
+
+        let mut started = false;
+        for _ in 0..60 {
+            world.step(DT);
+            started |= world.collision_events()
+                .iter()
+                .any(|event| matches!(event.event_type, CollisionEventType::Started) && !event.is_sensor);
+        }
+        assert!(started);
+        assert!(height(&world) < -1.0);
+
+        let stale = world.colliders(1)[0];
+        world.remove_entity(1).unwrap();
+        assert_eq!(world.set_solver_groups(stale, CollisionGroups::all()), Err(PhysicsError::InvalidColliderHandle(stale)));
+    }
+}
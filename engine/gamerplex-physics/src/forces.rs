@@ -1,32 +1,554 @@
 use gamerplex_math::Vector3;
+use rapier3d::parry::bounding_volume::BoundingVolume;
+use rapier3d::prelude::{
+    Collider,
+    ColliderHandle as RapierColliderHandle,
+    Point,
+    QueryFilter as RapierQueryFilter,
+    RigidBodyHandle,
+    Vector,
+};
+use serde::{Deserialize, Serialize};
 
-use crate::{EntityId, World};
+use crate::buoyancy;
+use crate::integration::rapier;
+use crate::{CollisionGroups, EntityId, PhysicsError, World};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ForceFieldKind {
+    // pulls bodies towards the air velocity, drag is in newtons per m/s of difference
+    Wind { velocity: Vector3, drag: f32 },
+    // one-shot radial impulse on the next substep, the field removes itself afterwards
+    Explosion { impulse: f32 },
+    // acceleration towards the field center, negative values push away
+    Attractor { strength: f32 },
+    // acceleration around `axis` through the field center plus a pull towards the axis
+    Vortex { axis: Vector3, strength: f32, inward: f32 },
+    // the water surface is level with the highest point of the sensors a collider
+    // overlaps, measured against gravity, and each collider is lifted by the weight of
+    // the water its submerged part displaces
+    Water { density: f32, linear_drag: f32, angular_drag: f32 },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Falloff {
+    Constant,
+    Linear, // fades to zero at the edge of the sensor's bounding sphere
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ForceFieldDef {
+    pub kind: ForceFieldKind,
+    pub falloff: Falloff, // only for explosions, attractors and vortices
+    pub groups: CollisionGroups, // only colliders that interact with these groups are affected
+}
+
+impl Default for ForceFieldDef {
+    fn default() -> Self {
+        Self {
+            kind: ForceFieldKind::Wind { velocity: Vector3::zeros(), drag: 1.0 },
+            falloff: Falloff::Constant,
+            groups: CollisionGroups::all(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct ForceField {
+    def: ForceFieldDef,
+    sensors: Vec<RapierColliderHandle>,
+}
 
 impl World {
-    pub fn apply_force(&mut self, entity: EntityId, force: Vector3) -> bool {
-        if let Some(handle) = self.entity_body_map.get(&entity) {
-            // Will apply force when integrated with Rapier
-            true
-        } else {
-            false
+    pub fn apply_force(&mut self, entity: EntityId, force: Vector3) -> Result<(), PhysicsError> {
+        let body = self.dynamic_body_mut(entity)?;
+        body.add_force(Vector::from(force), true);
+        Ok(())
+    }
+
+    pub fn apply_impulse(&mut self, entity: EntityId, impulse: Vector3) -> Result<(), PhysicsError> {
+        let body = self.dynamic_body_mut(entity)?;
+        body.apply_impulse(Vector::from(impulse), true);
+        Ok(())
+    }
+
+    pub fn apply_torque(&mut self, entity: EntityId, torque: Vector3) -> Result<(), PhysicsError> {
+        let body = self.dynamic_body_mut(entity)?;
+        body.add_torque(Vector::from(torque), true);
+        Ok(())
+    }
+
+    pub fn apply_torque_impulse(&mut self, entity: EntityId, torque_impulse: Vector3) -> Result<(), PhysicsError> {
+        let body = self.dynamic_body_mut(entity)?;
+        body.apply_torque_impulse(Vector::from(torque_impulse), true);
+        Ok(())
+    }
+
+    // `point` is in world space, an off-center force also produces torque
+    pub fn apply_force_at_point(&mut self, entity: EntityId, force: Vector3, point: Vector3) -> Result<(), PhysicsError> {
+        let body = self.dynamic_body_mut(entity)?;
+        body.add_force_at_point(Vector::from(force), Point::from(point), true);
+        Ok(())
+    }
+
+    pub fn apply_impulse_at_point(&mut self, entity: EntityId, impulse: Vector3, point: Vector3) -> Result<(), PhysicsError> {
+        let body = self.dynamic_body_mut(entity)?;
+        body.apply_impulse_at_point(Vector::from(impulse), Point::from(point), true);
+        Ok(())
+    }
+
+    pub fn set_linear_velocity(&mut self, entity: EntityId, velocity: Vector3) -> Result<(), PhysicsError> {
+        let body = self.dynamic_body_mut(entity)?;
+        body.set_linvel(Vector::from(velocity), true);
+        Ok(())
+    }
+
+    pub fn set_angular_velocity(&mut self, entity: EntityId, velocity: Vector3) -> Result<(), PhysicsError> {
+        let body = self.dynamic_body_mut(entity)?;
+        body.set_angvel(Vector::from(velocity), true);
+        Ok(())
+    }
+
+    pub fn linear_velocity(&self, entity: EntityId) -> Result<Vector3, PhysicsError> {
+        let body = self.rigid_body(entity)?;
+        Ok(Vector3::from(body.linvel()))
+    }
+
+    pub fn angular_velocity(&self, entity: EntityId) -> Result<Vector3, PhysicsError> {
+        let body = self.rigid_body(entity)?;
+        Ok(Vector3::from(body.angvel()))
+    }
+
+    // Clears the forces and torques accumulated with apply_force/apply_torque,
+    // they persist across steps until reset
+    pub fn reset_forces(&mut self, entity: EntityId) -> Result<(), PhysicsError> {
+        let body = self.dynamic_body_mut(entity)?;
+        body.reset_forces(true);
+        body.reset_torques(true);
+        Ok(())
+    }
+
+    pub fn wake_up(&mut self, entity: EntityId) -> Result<(), PhysicsError> {
+        let body = self.rigid_body_mut(entity)?;
+        body.wake_up(true);
+        Ok(())
+    }
+
+    // The sensor colliders on the entity's body become the field's volume, the field
+    // acts on every dynamic body overlapping them before each fixed step
+    pub fn add_force_field(&mut self, entity: EntityId, def: &ForceFieldDef) -> Result<(), PhysicsError> {
+        let sensors: Vec<_> = self.rigid_body(entity)?
+            .colliders()
+            .iter()
+            .copied()
+            .filter(|handle| self.collider_set[*handle].is_sensor())
+            .collect();
+
+        if sensors.is_empty() {
+            return Err(PhysicsError::NoSensorCollider(entity));
+        }
+
+        self.force_fields.insert(entity, ForceField { def: def.clone(), sensors });
+        Ok(())
+    }
+
+    pub fn remove_force_field(&mut self, entity: EntityId) -> bool {
+        self.force_fields.remove(&entity).is_some()
+    }
+
+    pub(crate) fn apply_force_fields(&mut self, dt: f32) {
+        let mut entities: Vec<EntityId> = self.force_fields.keys().copied().collect();
+        entities.sort_unstable();
+
+        for entity in entities {
+            let field = &self.force_fields[&entity];
+            let overlapping = self.force_field_overlaps(field);
+            let Some(volume) = field.sensors
+                .iter()
+                .filter_map(|sensor| self.collider_set.get(*sensor))
+                .map(|sensor| sensor.compute_aabb())
+                .reduce(|a, b| a.merged(&b))
+            else {
+                continue;
+            };
+            let def = field.def.clone();
+
+            if let ForceFieldKind::Water { density, linear_drag, angular_drag } = def.kind {
+                let Some(up) = (-Vector::from(self.gravity)).try_normalize(f32::EPSILON) else {
+                    continue;
+                };
+                // a collider in several sensors floats on the highest of them
+                let mut surfaces: Vec<(RapierColliderHandle, RigidBodyHandle, f32)> = Vec::new();
+                for (sensor, collider, body) in overlapping {
+                    let surface = water_surface(&self.collider_set[sensor], &up);
+                    match surfaces.last_mut() {
+                        Some(last) if last.0 == collider => last.2 = last.2.max(surface),
+                        _ => surfaces.push((collider, body, surface)),
+                    }
+                }
+                for (collider, body, surface) in surfaces {
+                    self.apply_buoyancy(collider, body, surface, &up, density, linear_drag, angular_drag, dt);
+                }
+                continue;
+            }
+
+            let mut bodies: Vec<RigidBodyHandle> = overlapping.into_iter().map(|(_, _, body)| body).collect();
+            bodies.sort_by_key(|handle| handle.into_raw_parts());
+            bodies.dedup();
+
+            let center = volume.center();
+            let radius = volume.bounding_sphere().radius;
+            for handle in bodies {
+                let body = &mut self.rigid_body_set[handle];
+                let offset = body.center_of_mass() - center;
+                let distance = offset.norm();
+                let scale = match def.falloff {
+                    Falloff::Constant => 1.0,
+                    Falloff::Linear => (1.0 - distance / radius).max(0.0),
+                };
+                let away = if distance > f32::EPSILON { offset / distance } else { Vector::zeros() };
+
+                let impulse = match &def.kind {
+                    ForceFieldKind::Wind { velocity, drag } => {
+                        // clamped so light bodies reach the wind speed instead of overshooting it
+                        let blend = (drag * dt * body.mass_properties().local_mprops.inv_mass).min(1.0);
+                        (Vector::from(*velocity) - body.linvel()) * body.mass() * blend
+                    }
+                    ForceFieldKind::Explosion { impulse } => away * *impulse * scale,
+                    ForceFieldKind::Attractor { strength } => -away * *strength * scale * body.mass() * dt,
+                    ForceFieldKind::Vortex { axis, strength, inward } => {
+                        let axis = Vector::from(axis.normalize());
+                        let radial = offset - axis * offset.dot(&axis);
+                        let radial = if radial.norm() > f32::EPSILON { radial.normalize() } else { Vector::zeros() };
+                        (axis.cross(&radial) * *strength - radial * *inward) * scale * body.mass() * dt
+                    }
+                    ForceFieldKind::Water { .. } => unreachable!(),
+                };
+                body.apply_impulse(impulse, true);
+            }
+
+            if matches!(def.kind, ForceFieldKind::Explosion { .. }) {
+                self.force_fields.remove(&entity);
+            }
+        }
+    }
+
+    // Dynamic bodies overlapping any of the field's sensors as (sensor, collider, body),
+    // sorted by collider. Goes through the scene queries so a field added this frame acts on the next substep,
+    // bodies added since the last step are not seen yet.
+    fn force_field_overlaps(&self, field: &ForceField) -> Vec<(RapierColliderHandle, RapierColliderHandle, RigidBodyHandle)> {
+        let filter = RapierQueryFilter::only_dynamic().exclude_sensors();
+        let mut overlapping = Vec::new();
+
+        for (sensor_handle, sensor) in field.sensors.iter().filter_map(|handle| Some((*handle, self.collider_set.get(*handle)?))) {
+            self.query_pipeline.intersections_with_shape(
+                &self.rigid_body_set,
+                &self.collider_set,
+                sensor.position(),
+                sensor.shape(),
+                filter,
+                |handle| {
+                    let collider = &self.collider_set[handle];
+                    if field.def.groups.interacts_with(&rapier::from_interaction_groups(collider.collision_groups())) {
+                        if let Some(body) = collider.parent() {
+                            overlapping.push((sensor_handle, handle, body));
+                        }
+                    }
+                    true
+                },
+            );
+        }
+
+        overlapping.sort_by_key(|(sensor, collider, _)| (collider.into_raw_parts(), sensor.into_raw_parts()));
+        overlapping
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn apply_buoyancy(
+        &mut self,
+        collider: RapierColliderHandle,
+        body: RigidBodyHandle,
+        surface: f32,
+        up: &Vector<f32>,
+        density: f32,
+        linear_drag: f32,
+        angular_drag: f32,
+        dt: f32,
+    ) {
+        let collider = &self.collider_set[collider];
+        let submerged = buoyancy::submerged_part(collider.shape(), collider.position(), up, surface);
+        let volume = collider.volume();
+        if submerged.volume <= f32::EPSILON || volume <= f32::EPSILON {
+            return;
+        }
+        let fraction = (submerged.volume / volume).min(1.0);
+        let gravity = Vector::from(self.gravity);
+
+        let body = &mut self.rigid_body_set[body];
+        let point = submerged.centroid;
+        // pushing at the center of the submerged part rights tilted bodies
+        body.apply_impulse_at_point(-gravity * density * submerged.volume * dt, point, true);
+
+        let inv_mass = body.mass_properties().local_mprops.inv_mass;
+        let linear_blend = (linear_drag * fraction * dt * inv_mass).min(1.0);
+        let drag = -body.velocity_at_point(&point) * body.mass() * linear_blend;
+        body.apply_impulse_at_point(drag, point, true);
+
+        let angular_blend = (angular_drag * fraction * dt).min(1.0);
+        let angvel = *body.angvel();
+        body.set_angvel(angvel * (1.0 - angular_blend), true);
+    }
+}
+
+// Height of the sensor's highest point along `up`
+fn water_surface(sensor: &Collider, up: &Vector<f32>) -> f32 {
+    match sensor.shape().as_support_map() {
+        Some(shape) => up.dot(&shape.support_point(sensor.position(), up).coords),
+        None => sensor.compute_aabb().vertices().iter().map(|corner| up.dot(&corner.coords)).fold(f32::MIN, f32::max),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, ColliderShape, CollisionLayers};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    // A fixed body 0 and dynamic unit boxes 1 and 2 at x = 5 and x = 10, without gravity
+    fn plain_world() -> World {
+        let mut world = World::new(Vector3::zeros());
+        let fixed = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, fixed, &ColliderDef::default()).unwrap();
+
+        for (entity, x) in [(1, 5.0), (2, 10.0)] {
+            let body = world.add_rigid_body(entity, &Body { position: Vector3::new(x, 0.0, 0.0), ..Default::default() });
+            world.add_collider(entity, body, &ColliderDef::default()).unwrap();
         }
+        world
     }
-    
-    pub fn apply_impulse(&mut self, entity: EntityId, impulse: Vector3) -> bool {
-        if let Some(handle) = self.entity_body_map.get(&entity) {
-            // Will apply impulse when integrated with Rapier  
-            true
-        } else {
-            false
+
+    // The field volume is entity 0, the boxes are 1, 2, ... at the given positions
+    fn field_world(gravity: Vector3, half_extents: Vector3, def: &ForceFieldDef, boxes: &[(Vector3, f32)]) -> World {
+        let mut world = World::new(gravity);
+        let volume = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, volume, &ColliderDef {
+            shape: ColliderShape::Box { half_extents },
+            is_sensor: true,
+            ..Default::default()
+        }).unwrap();
+
+        for (entity, (position, density)) in (1..).zip(boxes) {
+            let body = world.add_rigid_body(entity, &Body { position: *position, ..Default::default() });
+            world.add_collider(entity, body, &ColliderDef { density: *density, ..Default::default() }).unwrap();
         }
+
+        // scene queries only know the boxes after a step
+        world.step(DT);
+        world.add_force_field(0, def).unwrap();
+        world
     }
-    
-    pub fn set_linear_velocity(&mut self, entity: EntityId, velocity: Vector3) -> bool {
-        if let Some(handle) = self.entity_body_map.get(&entity) {
-            // Will set velocity when integrated with Rapier
-            true
-        } else {
-            false
+
+    fn position(world: &World, entity: EntityId) -> Vector3 {
+        Vector3::from(world.rigid_body(entity).unwrap().translation())
+    }
+
+    #[test]
+    fn forces_need_a_dynamic_body() {
+        let mut world = plain_world();
+        let not_dynamic = Err(PhysicsError::BodyNotDynamic(0));
+        assert_eq!(world.apply_force(0, Vector3::unit_x()), not_dynamic);
+        assert_eq!(world.apply_impulse_at_point(0, Vector3::unit_x(), Vector3::zeros()), not_dynamic);
+        assert_eq!(world.apply_torque(0, Vector3::unit_x()), not_dynamic);
+        assert_eq!(world.set_linear_velocity(0, Vector3::unit_x()), not_dynamic);
+        assert_eq!(world.reset_forces(0), not_dynamic);
+        assert_eq!(world.linear_velocity(0), Ok(Vector3::zeros()));
+
+        let missing = Err(PhysicsError::NoRigidBody(9));
+        assert_eq!(world.apply_impulse(9, Vector3::unit_x()), missing);
+        assert_eq!(world.set_angular_velocity(9, Vector3::unit_x()), missing);
+        assert_eq!(world.wake_up(9), missing);
+        assert_eq!(world.angular_velocity(9), Err(PhysicsError::NoRigidBody(9)));
+    }
+
+    #[test]
+    fn torques_and_off_center_forces_spin() {
+        let mut world = plain_world();
+        world.apply_torque(1, Vector3::unit_z()).unwrap();
+        world.apply_force_at_point(2, Vector3::unit_y(), Vector3::new(11.0, 0.0, 0.0)).unwrap();
+        world.step(DT);
+
+        assert!(world.angular_velocity(1).unwrap().z > 0.0);
+        assert_eq!(world.linear_velocity(1).unwrap(), Vector3::zeros());
+        assert!(world.linear_velocity(2).unwrap().y > 0.0);
+        assert!(world.angular_velocity(2).unwrap().z > 0.0);
+
+        // forces stay applied until they are reset
+        world.reset_forces(1).unwrap();
+        let spin = world.angular_velocity(1).unwrap().z;
+        let pushed_spin = world.angular_velocity(2).unwrap().z;
+        world.step(DT);
+        assert!((world.angular_velocity(1).unwrap().z - spin).abs() < 1e-5);
+        assert!(world.angular_velocity(2).unwrap().z > pushed_spin);
+    }
+
+    #[test]
+    fn light_boxes_float_heavy_boxes_sink() {
+        let water = ForceFieldDef {
+            kind: ForceFieldKind::Water { density: 1.0, linear_drag: 2.0, angular_drag: 1.0 },
+            ..Default::default()
+        };
+        // surface at y = 10
+        let mut world = field_world(Vector3::new(0.0, -9.81, 0.0), Vector3::new(20.0, 10.0, 20.0), &water, &[
+            (Vector3::new(-3.0, 10.0, 0.0), 0.5),
+            (Vector3::new(3.0, 10.0, 0.0), 3.0),
+        ]);
+
+        for _ in 0..600 {
+            world.step(DT);
         }
+
+        // half of the unit box is under water at half the water density
+        assert!((position(&world, 1).y - 10.0).abs() < 0.05, "floater at {:?}", position(&world, 1));
+        assert!(position(&world, 2).y < 7.0, "sinker at {:?}", position(&world, 2));
+    }
+
+    #[test]
+    fn spheres_and_boxes_float_at_their_predicted_depth() {
+        let water = ForceFieldDef {
+            kind: ForceFieldKind::Water { density: 1.0, linear_drag: 2.0, angular_drag: 1.0 },
+            ..Default::default()
+        };
+        // surface at y = 10
+        let mut world = field_world(Vector3::new(0.0, -9.81, 0.0), Vector3::new(20.0, 10.0, 20.0), &water, &[]);
+        let radius = 0.5;
+        let side = (4.0 / 3.0 * std::f32::consts::PI * radius * radius * radius).cbrt();
+        for (entity, shape, x) in [
+            (1, ColliderShape::Sphere { radius }, -3.0),
+            (2, ColliderShape::Box { half_extents: Vector3::new(side, side, side) * 0.5 }, 3.0),
+        ] {
+            let body = world.add_rigid_body(entity, &Body { position: Vector3::new(x, 10.0, 0.0), ..Default::default() });
+            world.add_collider(entity, body, &ColliderDef { shape, density: 0.15, ..Default::default() }).unwrap();
+        }
+
+        for _ in 0..600 {
+            world.step(DT);
+        }
+
+        // 15% of each is under water: a 0.2444 m deep cap of the sphere, 0.15 of the box's side
+        let sphere = position(&world, 1).y - 10.0;
+        let cube = position(&world, 2).y - 10.0;
+        assert!((sphere - (radius - 0.2444)).abs() < 0.01, "sphere center {} above the surface", sphere);
+        assert!((cube - side * 0.35).abs() < 0.01, "box center {} above the surface", cube);
+    }
+
+    #[test]
+    fn water_made_of_two_sensors_lifts_once() {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+        let volume = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        for x in [-10.0, 10.0] {
+            world.add_collider(0, volume, &ColliderDef {
+                shape: ColliderShape::Box { half_extents: Vector3::new(10.0, 10.0, 20.0) },
+                position: Vector3::new(x, 0.0, 0.0),
+                is_sensor: true,
+                ..Default::default()
+            }).unwrap();
+        }
+        // straddles the seam between the two sensors
+        let body = world.add_rigid_body(1, &Body { position: Vector3::new(0.0, 10.0, 0.0), ..Default::default() });
+        world.add_collider(1, body, &ColliderDef { density: 0.5, ..Default::default() }).unwrap();
+        world.step(DT);
+        world.add_force_field(0, &ForceFieldDef {
+            kind: ForceFieldKind::Water { density: 1.0, linear_drag: 2.0, angular_drag: 1.0 },
+            ..Default::default()
+        }).unwrap();
+
+        for _ in 0..600 {
+            world.step(DT);
+        }
+        assert!((position(&world, 1).y - 10.0).abs() < 0.05, "floater at {:?}", position(&world, 1));
+    }
+
+    #[test]
+    fn fields_only_act_on_matching_groups() {
+        let mut layers = CollisionLayers::new();
+        let (debris, player) = (layers.define("debris").unwrap(), layers.define("player").unwrap());
+        let wind = ForceFieldDef {
+            kind: ForceFieldKind::Wind { velocity: Vector3::new(5.0, 0.0, 0.0), drag: 2.0 },
+            groups: layers.groups(&["debris", "player"], &["debris"]).unwrap(),
+            ..Default::default()
+        };
+        let mut world = field_world(Vector3::zeros(), Vector3::new(5.0, 5.0, 5.0), &wind, &[]);
+        for (entity, groups) in [(1, CollisionGroups::new(debris, u32::MAX)), (2, CollisionGroups::new(player, u32::MAX))] {
+            let body = world.add_rigid_body(entity, &Body { position: Vector3::new(0.0, entity as f32 * 2.0, 0.0), ..Default::default() });
+            world.add_collider(entity, body, &ColliderDef { collision_groups: groups, ..Default::default() }).unwrap();
+        }
+
+        for _ in 0..10 {
+            world.step(DT);
+        }
+        assert!(world.linear_velocity(1).unwrap().x > 0.0);
+        assert_eq!(world.linear_velocity(2).unwrap(), Vector3::zeros());
+    }
+
+    #[test]
+    fn wind_only_blows_inside_its_volume() {
+        let wind = ForceFieldDef {
+            kind: ForceFieldKind::Wind { velocity: Vector3::new(5.0, 0.0, 0.0), drag: 2.0 },
+            ..Default::default()
+        };
+        let mut world = field_world(Vector3::zeros(), Vector3::new(2.0, 2.0, 2.0), &wind, &[
+            (Vector3::zeros(), 1.0),
+            (Vector3::new(0.0, 10.0, 0.0), 1.0),
+        ]);
+
+        for _ in 0..30 {
+            world.step(DT);
+        }
+
+        let velocity = world.linear_velocity(1).unwrap();
+        assert!(velocity.x > 1.0 && velocity.x <= 5.0, "{:?}", velocity);
+        assert_eq!(world.linear_velocity(2).unwrap(), Vector3::zeros());
+    }
+
+    #[test]
+    fn explosions_push_outwards_once() {
+        let explosion = ForceFieldDef {
+            kind: ForceFieldKind::Explosion { impulse: 10.0 },
+            falloff: Falloff::Linear,
+            ..Default::default()
+        };
+        let mut world = field_world(Vector3::zeros(), Vector3::new(5.0, 5.0, 5.0), &explosion, &[
+            (Vector3::new(2.0, 0.0, 0.0), 1.0),
+            (Vector3::new(-4.0, 0.0, 0.0), 1.0),
+        ]);
+
+        world.step(DT);
+        let near = world.linear_velocity(1).unwrap();
+        let far = world.linear_velocity(2).unwrap();
+        assert!(near.x > 0.0 && far.x < 0.0);
+        assert!(near.x > -far.x);
+
+        world.step(DT);
+        assert_eq!(world.linear_velocity(1).unwrap(), near);
+        assert!(!world.remove_force_field(0));
+    }
+
+    #[test]
+    fn attractors_pull_and_vortices_spin() {
+        let attractor = ForceFieldDef { kind: ForceFieldKind::Attractor { strength: 10.0 }, ..Default::default() };
+        let mut world = field_world(Vector3::zeros(), Vector3::new(5.0, 5.0, 5.0), &attractor, &[(Vector3::new(3.0, 0.0, 0.0), 1.0)]);
+        world.step(DT);
+        assert!(world.linear_velocity(1).unwrap().x < 0.0);
+
+        let vortex = ForceFieldDef {
+            kind: ForceFieldKind::Vortex { axis: Vector3::unit_y(), strength: 10.0, inward: 0.0 },
+            ..Default::default()
+        };
+        let mut world = field_world(Vector3::zeros(), Vector3::new(5.0, 5.0, 5.0), &vortex, &[(Vector3::new(3.0, 0.0, 0.0), 1.0)]);
+        world.step(DT);
+        let velocity = world.linear_velocity(1).unwrap();
+        // counter-clockwise around +y, so +x moves towards -z
+        assert!(velocity.z < 0.0 && velocity.x.abs() < 1e-4, "{:?}", velocity);
     }
-}
\ No newline at end of file
+}
@@ -1,64 +1,201 @@
-use rapier3d::prelude::RigidBodyHandle;
+use rapier3d::prelude::{ColliderHandle as RapierColliderHandle, ImpulseJointHandle, RigidBodyHandle};
+use serde::{Deserialize, Serialize};
 
 pub type EntityId = u32; // Or whatever your ECS uses
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-pub struct BodyHandle(pub u32);
+// Handles mirror Rapier's arena index + generation, so a handle to a removed
+// body never resolves to whatever body later reuses its slot
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BodyHandle {
+    index: u32,
+    generation: u32,
+}
 
 impl BodyHandle {
-    pub fn new(id: u32) -> Self {
-        Self(id)
-    }
-    
     pub fn invalid() -> Self {
-        Self(u32::MAX)
+        Self::from_rapier_handle(RigidBodyHandle::invalid())
     }
-    
+
     pub fn is_valid(&self) -> bool {
-        self.0 != u32::MAX
+        *self != Self::invalid()
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
     }
 
     pub fn into_raw_parts(self) -> (u32, u32) {
-        (self.0, 0) // The second value is the generation, which we set to 0
+        (self.index, self.generation)
     }
 
-    pub fn from_raw_parts(index: u32, _generation: u32) -> Self {
-        Self(index)
+    pub fn from_raw_parts(index: u32, generation: u32) -> Self {
+        Self { index, generation }
     }
 
     pub fn to_rapier_handle(&self) -> RigidBodyHandle {
-        RigidBodyHandle::from_raw_parts(self.0, 0)
+        RigidBodyHandle::from_raw_parts(self.index, self.generation)
     }
-    
-    // Create your handle from Rapier's handle
+
     pub fn from_rapier_handle(handle: RigidBodyHandle) -> Self {
-        Self(handle.into_raw_parts().0)
+        let (index, generation) = handle.into_raw_parts();
+        Self { index, generation }
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-pub struct ColliderHandle(pub u32);
+impl From<RigidBodyHandle> for BodyHandle {
+    fn from(handle: RigidBodyHandle) -> Self {
+        Self::from_rapier_handle(handle)
+    }
+}
+
+impl From<BodyHandle> for RigidBodyHandle {
+    fn from(handle: BodyHandle) -> Self {
+        handle.to_rapier_handle()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ColliderHandle {
+    index: u32,
+    generation: u32,
+}
 
 impl ColliderHandle {
-    pub fn new(id: u32) -> Self {
-        Self(id)
+    pub fn invalid() -> Self {
+        Self::from_rapier_handle(RapierColliderHandle::invalid())
     }
-    
+
+    pub fn is_valid(&self) -> bool {
+        *self != Self::invalid()
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn into_raw_parts(self) -> (u32, u32) {
+        (self.index, self.generation)
+    }
+
+    pub fn from_raw_parts(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    pub fn to_rapier_handle(&self) -> RapierColliderHandle {
+        RapierColliderHandle::from_raw_parts(self.index, self.generation)
+    }
+
+    pub fn from_rapier_handle(handle: RapierColliderHandle) -> Self {
+        let (index, generation) = handle.into_raw_parts();
+        Self { index, generation }
+    }
+}
+
+impl From<RapierColliderHandle> for ColliderHandle {
+    fn from(handle: RapierColliderHandle) -> Self {
+        Self::from_rapier_handle(handle)
+    }
+}
+
+impl From<ColliderHandle> for RapierColliderHandle {
+    fn from(handle: ColliderHandle) -> Self {
+        handle.to_rapier_handle()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct JointHandle {
+    index: u32,
+    generation: u32,
+}
+
+impl JointHandle {
     pub fn invalid() -> Self {
-        Self(u32::MAX)
+        Self::from_rapier_handle(ImpulseJointHandle::invalid())
     }
-    
+
     pub fn is_valid(&self) -> bool {
-        self.0 != u32::MAX
+        *self != Self::invalid()
     }
 
     pub fn into_raw_parts(self) -> (u32, u32) {
-        (self.0, 0) 
+        (self.index, self.generation)
+    }
+
+    pub fn from_raw_parts(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    pub fn to_rapier_handle(&self) -> ImpulseJointHandle {
+        ImpulseJointHandle::from_raw_parts(self.index, self.generation)
+    }
+
+    pub fn from_rapier_handle(handle: ImpulseJointHandle) -> Self {
+        let (index, generation) = handle.into_raw_parts();
+        Self { index, generation }
     }
+}
+
+impl From<ImpulseJointHandle> for JointHandle {
+    fn from(handle: ImpulseJointHandle) -> Self {
+        Self::from_rapier_handle(handle)
+    }
+}
+
+impl From<JointHandle> for ImpulseJointHandle {
+    fn from(handle: JointHandle) -> Self {
+        handle.to_rapier_handle()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, ColliderDef, PhysicsError, World};
+    use gamerplex_math::Vector3;
 
-    // Static method to create from raw parts
-    pub fn from_raw_parts(index: u32, _generation: u32) -> Self {
-        //ignore the generation for now
-        Self(index)
+    #[test]
+    fn handles_round_trip_through_rapier() {
+        let rapier_body = RigidBodyHandle::from_raw_parts(7, 3);
+        let body = BodyHandle::from(rapier_body);
+        assert_eq!(body.into_raw_parts(), (7, 3));
+        assert_eq!(RigidBodyHandle::from(body), rapier_body);
+
+        let rapier_collider = RapierColliderHandle::from_raw_parts(2, 9);
+        let collider = ColliderHandle::from(rapier_collider);
+        assert_eq!(collider.into_raw_parts(), (2, 9));
+        assert_eq!(RapierColliderHandle::from(collider), rapier_collider);
+    }
+
+    #[test]
+    fn stale_handles_are_rejected_after_reuse() {
+        let mut world = World::new(Vector3::zeros());
+        let old = world.add_rigid_body(1, &Body::default());
+        world.remove_rigid_body(old).unwrap();
+
+        let new = world.add_rigid_body(2, &Body::default());
+        assert_eq!(new.into_raw_parts().0, old.into_raw_parts().0);
+        assert_ne!(new, old);
+
+        assert_eq!(world.body_entity(old), None);
+        assert_eq!(world.body_entity(new), Some(2));
+        assert_eq!(world.add_collider(1, old, &ColliderDef::default()), Err(PhysicsError::InvalidBodyHandle(old)));
+        assert!(world.colliders(2).is_empty());
+        assert_eq!(world.remove_rigid_body(old), Err(PhysicsError::InvalidBodyHandle(old)));
+    }
+
+    #[test]
+    fn invalid_handles() {
+        assert!(!BodyHandle::invalid().is_valid());
+        assert!(!ColliderHandle::invalid().is_valid());
+        assert!(BodyHandle::from_raw_parts(0, 0).is_valid());
     }
-}
\ No newline at end of file
+}
@@ -1,9 +1,11 @@
 use gamerplex_math::{Vector3, Quaternion};
 
 use crate::body::{BodyType, Body};
-use crate::collider::{ColliderDef, ColliderShape};
+use crate::character::{CharacterControllerDef, CharacterMovement};
+use crate::collider::ColliderDef;
+use crate::filter::PhysicsHooks;
 use crate::handles::{BodyHandle, ColliderHandle};
-use crate::{world::World, handles::EntityId};
+use crate::{world::World, handles::EntityId, error::PhysicsError};
 
 // Component definitions for your ECS
 pub struct RigidBodyComponent {
@@ -37,14 +39,49 @@ impl PhysicsSystem {
     pub fn sync_transforms(&self) -> Vec<(EntityId, Vector3, Quaternion)> {
         self.world.synchronize_transforms()
     }
+
+    // Same as sync_transforms but blended between the last two physics steps
+    pub fn sync_interpolated_transforms(&self) -> Vec<(EntityId, Vector3, Quaternion)> {
+        self.world.synchronize_interpolated_transforms()
+    }
     
     // Methods to add physics components to entities
     pub fn add_rigid_body(&mut self, entity: EntityId, def: &Body) -> BodyHandle {
         self.world.add_rigid_body(entity, def)
     }
     
-    pub fn add_collider(&mut self, entity: EntityId, body_handle: BodyHandle, def: &ColliderDef) -> ColliderHandle {
+    pub fn add_collider(&mut self, entity: EntityId, body_handle: BodyHandle, def: &ColliderDef) -> Result<ColliderHandle, PhysicsError> {
         self.world.add_collider(entity, body_handle, def)
     }
+
+    pub fn add_character_controller(&mut self, entity: EntityId, def: &CharacterControllerDef) -> Result<(), PhysicsError> {
+        self.world.add_character_controller(entity, def)
+    }
+
+    pub fn move_character(&mut self, entity: EntityId, desired_translation: Vector3, delta_time: f32) -> Result<CharacterMovement, PhysicsError> {
+        self.world.move_character(entity, desired_translation, delta_time)
+    }
+
+    pub fn set_physics_hooks(&mut self, hooks: Option<Box<dyn PhysicsHooks>>) {
+        self.world.set_physics_hooks(hooks)
+    }
+
+    // Rollback: save before simulating predicted frames, restore when the real inputs arrive
+    pub fn snapshot(&self) -> Result<Vec<u8>, PhysicsError> {
+        self.world.snapshot()
+    }
+
+    pub fn restore(&mut self, snapshot: &[u8]) -> Result<(), PhysicsError> {
+        self.world.restore(snapshot)
+    }
+
+    pub fn state_checksum(&self) -> u64 {
+        self.world.state_checksum()
+    }
+
+    // Call when the entity is despawned
+    pub fn remove_entity(&mut self, entity: EntityId) -> Result<(), PhysicsError> {
+        self.world.remove_entity(entity)
+    }
     
 }
\ No newline at end of file
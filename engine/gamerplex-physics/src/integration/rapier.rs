@@ -1,27 +1,19 @@
 use rapier3d::prelude::*;
-use crate::body::{BodyType, Body};
+use rapier3d::parry::transformation::vhacd::{VHACD, VHACDParameters};
+use rapier3d::control::{CharacterAutostep, CharacterLength, DynamicRayCastVehicleController, KinematicCharacterController, WheelTuning};
+use crate::body::{AxisLocks, BodyType, Body, MassMode};
 use crate::collider::{ColliderDef, ColliderShape};
-use crate::handles::BodyHandle;
+use crate::filter::CollisionGroups;
+use crate::character::CharacterControllerDef;
+use crate::vehicle::VehicleDef;
+use crate::settings::SimulationSettings;
+use crate::error::PhysicsError;
+use crate::joint::{JointAxis as GpJointAxis, JointDef, JointInfo, JointLimit, JointMotor, JointType, MotorTarget};
+use crate::material::MaterialId;
 use gamerplex_math::{Vector3, Quaternion};
 
-// Conversion utilities between your math types and nalgebra
-pub fn to_na_vector(v: &Vector3) -> nalgebra::Vector3<f32> {
-    nalgebra::Vector3::new(v.x, v.y, v.z)
-}
-
-pub fn from_na_vector(v: &nalgebra::Vector3<f32>) -> Vector3 {
-    Vector3::new(v.x, v.y, v.z)
-}
-
-pub fn to_na_quaternion(q: &Quaternion) -> nalgebra::UnitQuaternion<f32> {
-    nalgebra::UnitQuaternion::new_normalize(
-        nalgebra::Quaternion::new(q.w, q.x, q.y, q.z)
-    )
-}
-
-pub fn from_na_quaternion(q: &nalgebra::UnitQuaternion<f32>) -> Quaternion {
-    Quaternion::new(q.i, q.j, q.k, q.w)
-}
+// Vectors, points, rotations and isometries convert through the From impls in
+// gamerplex-math's `rapier` feature
 
 // Convert our body type to Rapier's
 pub fn convert_body_type(body_type: &BodyType) -> RigidBodyType {
@@ -35,27 +27,76 @@ pub fn convert_body_type(body_type: &BodyType) -> RigidBodyType {
 // Create a Rapier rigid body from our definition
 pub fn create_rigid_body(def: &Body) -> RigidBodyBuilder {
     let mut builder = RigidBodyBuilder::new(convert_body_type(&def.body_type))
-        .translation(to_na_vector(&def.position))
-        .rotation(to_na_vector(&def.rotation.to_euler_angles()))
+        .position(Isometry::from_parts(def.position.into(), def.rotation.into()))
         .linear_damping(def.linear_damping)
         .angular_damping(def.angular_damping)
         .can_sleep(def.can_sleep)
-        .ccd_enabled(def.ccd_enabled);
+        .ccd_enabled(def.ccd_enabled)
+        .soft_ccd_prediction(def.soft_ccd_prediction)
+        .locked_axes(convert_axis_locks(&def.locked_axes))
+        .gravity_scale(def.gravity_scale)
+        .dominance_group(def.dominance_group);
     
+    // the mass mode is applied by World once the body is in the set
     if def.body_type == BodyType::Dynamic {
         builder = builder
-            .additional_mass(def.mass)
-            .linvel(to_na_vector(&def.linear_velocity))
-            .angvel(to_na_vector(&def.angular_velocity));
+            .linvel(Vector::from(def.linear_velocity))
+            .angvel(Vector::from(def.angular_velocity));
     }
     
     builder
 }
 
-// Create a Rapier collider from our definition
-pub fn create_collider(def: &ColliderDef) -> ColliderBuilder {
-    // Convert shape to Rapier's shape
-    let shape = match &def.shape {
+// Reads a body back into a definition, for exporting scenes
+pub fn body_def(body: &RigidBody, mass: MassMode) -> Body {
+    Body {
+        body_type: match body.body_type() {
+            RigidBodyType::Dynamic => BodyType::Dynamic,
+            RigidBodyType::Fixed => BodyType::Static,
+            RigidBodyType::KinematicPositionBased | RigidBodyType::KinematicVelocityBased => BodyType::Kinematic,
+        },
+        position: Vector3::from(body.translation()),
+        rotation: Quaternion::from(body.rotation()),
+        linear_velocity: Vector3::from(body.linvel()),
+        angular_velocity: Vector3::from(body.angvel()),
+        mass,
+        locked_axes: axis_locks(body.locked_axes()),
+        gravity_scale: body.gravity_scale(),
+        dominance_group: body.dominance_group(),
+        linear_damping: body.linear_damping(),
+        angular_damping: body.angular_damping(),
+        // bodies that cannot sleep have negative thresholds
+        can_sleep: body.activation().normalized_linear_threshold >= 0.0,
+        ccd_enabled: body.is_ccd_enabled(),
+        soft_ccd_prediction: body.soft_ccd_prediction(),
+    }
+}
+
+pub fn convert_axis_locks(locks: &AxisLocks) -> LockedAxes {
+    let mut locked = LockedAxes::empty();
+    locked.set(LockedAxes::TRANSLATION_LOCKED_X, locks.translation_x);
+    locked.set(LockedAxes::TRANSLATION_LOCKED_Y, locks.translation_y);
+    locked.set(LockedAxes::TRANSLATION_LOCKED_Z, locks.translation_z);
+    locked.set(LockedAxes::ROTATION_LOCKED_X, locks.rotation_x);
+    locked.set(LockedAxes::ROTATION_LOCKED_Y, locks.rotation_y);
+    locked.set(LockedAxes::ROTATION_LOCKED_Z, locks.rotation_z);
+    locked
+}
+
+pub fn axis_locks(locked: LockedAxes) -> AxisLocks {
+    AxisLocks {
+        translation_x: locked.contains(LockedAxes::TRANSLATION_LOCKED_X),
+        translation_y: locked.contains(LockedAxes::TRANSLATION_LOCKED_Y),
+        translation_z: locked.contains(LockedAxes::TRANSLATION_LOCKED_Z),
+        rotation_x: locked.contains(LockedAxes::ROTATION_LOCKED_X),
+        rotation_y: locked.contains(LockedAxes::ROTATION_LOCKED_Y),
+        rotation_z: locked.contains(LockedAxes::ROTATION_LOCKED_Z),
+    }
+}
+
+// Convert shape to Rapier's shape
+pub fn create_shape(shape: &ColliderShape) -> Result<SharedShape, PhysicsError> {
+    let shape = match shape {
         ColliderShape::Sphere { radius } => {
             SharedShape::ball(*radius)
         },
@@ -74,37 +115,541 @@ pub fn create_collider(def: &ColliderDef) -> ColliderBuilder {
         },
         ColliderShape::ConvexHull { points } => {
             let na_points: Vec<nalgebra::Point3<f32>> = points.iter()
-                .map(|p| nalgebra::Point3::new(p.x, p.y, p.z))
+                .map(|point| Point::from(*point))
                 .collect();
             
-            SharedShape::convex_hull(&na_points).unwrap_or(
-                // Fallback to a sphere if convex hull fails
-                SharedShape::ball(1.0)
+            SharedShape::convex_hull(&na_points).ok_or_else(|| {
+                PhysicsError::InvalidShape(format!("convex hull of {} points is degenerate", points.len()))
+            })?
+        },
+        ColliderShape::Cone { height, radius } => {
+            SharedShape::cone(*height / 2.0, *radius)
+        },
+        ColliderShape::Segment { a, b } => {
+            SharedShape::segment(Point::from(*a), Point::from(*b))
+        },
+        ColliderShape::RoundBox { half_extents, border_radius } => {
+            let inner = check_border_radius(half_extents.x.min(half_extents.y).min(half_extents.z), *border_radius)?;
+            SharedShape::round_cuboid(
+                half_extents.x - inner,
+                half_extents.y - inner,
+                half_extents.z - inner,
+                inner
+            )
+        },
+        ColliderShape::RoundCylinder { height, radius, border_radius } => {
+            let inner = check_border_radius((height / 2.0).min(*radius), *border_radius)?;
+            SharedShape::round_cylinder(height / 2.0 - inner, radius - inner, inner)
+        },
+        ColliderShape::RoundCone { height, radius, border_radius } => {
+            let inner = check_border_radius((height / 2.0).min(*radius), *border_radius)?;
+            SharedShape::round_cone(height / 2.0 - inner, radius - inner, inner)
+        },
+        ColliderShape::TriMesh { vertices, indices } => {
+            check_mesh(vertices, indices)?;
+            let na_vertices = vertices.iter().map(|point| Point::from(*point)).collect();
+
+            SharedShape::trimesh(na_vertices, indices.clone())
+                .map_err(|err| PhysicsError::InvalidShape(format!("triangle mesh: {:?}", err)))?
+        },
+        ColliderShape::HeightField { heights, rows, columns, scale } => {
+            if *rows < 2 || *columns < 2 || heights.len() != rows * columns {
+                return Err(PhysicsError::InvalidShape(format!(
+                    "heightfield needs at least 2x2 heights and rows * columns of them, got {}x{} with {}",
+                    rows, columns, heights.len()
+                )));
+            }
+
+            SharedShape::heightfield(
+                nalgebra::DMatrix::from_row_slice(*rows, *columns, heights),
+                Vector::from(*scale)
             )
         },
+        ColliderShape::Compound { parts } => {
+            if parts.is_empty() {
+                return Err(PhysicsError::InvalidShape("compound shape without parts".to_string()));
+            }
+
+            let mut shapes = Vec::with_capacity(parts.len());
+            for part in parts {
+                let shape = create_shape(&part.shape)?;
+                // parry cannot nest composite shapes
+                if shape.as_composite_shape().is_some() {
+                    return Err(PhysicsError::InvalidShape("compound parts must not be composite shapes".to_string()));
+                }
+                shapes.push((Isometry::from_parts(part.position.into(), part.rotation.into()), shape));
+            }
+
+            SharedShape::compound(shapes)
+        },
+        ColliderShape::ConvexDecomposition { vertices, indices } => {
+            check_mesh(vertices, indices)?;
+            let na_vertices: Vec<nalgebra::Point3<f32>> = vertices.iter().map(|point| Point::from(*point)).collect();
+
+            let decomposition = VHACD::decompose(&VHACDParameters::default(), &na_vertices, indices, true);
+            let parts: Vec<(Isometry<f32>, SharedShape)> = decomposition
+                .compute_exact_convex_hulls(&na_vertices, indices)
+                .into_iter()
+                .filter_map(|(points, indices)| SharedShape::convex_mesh(points, &indices))
+                .map(|hull| (Isometry::identity(), hull))
+                .collect();
+
+            if parts.is_empty() {
+                return Err(PhysicsError::InvalidShape("convex decomposition produced no hulls".to_string()));
+            }
+            SharedShape::compound(parts)
+        },
     };
-    
-    let mut builder = ColliderBuilder::new(shape)
+
+    Ok(shape)
+}
+
+fn check_border_radius(max: f32, border_radius: f32) -> Result<f32, PhysicsError> {
+    if border_radius < 0.0 || border_radius > max {
+        return Err(PhysicsError::InvalidShape(format!(
+            "border radius {} must be between 0 and {}", border_radius, max
+        )));
+    }
+    Ok(border_radius)
+}
+
+fn check_mesh(vertices: &[Vector3], indices: &[[u32; 3]]) -> Result<(), PhysicsError> {
+    if indices.is_empty() {
+        return Err(PhysicsError::InvalidShape("mesh without triangles".to_string()));
+    }
+    if let Some(index) = indices.iter().flatten().find(|index| **index as usize >= vertices.len()) {
+        return Err(PhysicsError::InvalidShape(format!(
+            "mesh index {} out of bounds for {} vertices", index, vertices.len()
+        )));
+    }
+    Ok(())
+}
+
+// Create a Rapier collider from our definition, the material is applied by the world
+pub fn create_collider(def: &ColliderDef) -> Result<ColliderBuilder, PhysicsError> {
+    let mut events = ActiveEvents::COLLISION_EVENTS;
+    if def.contact_force_threshold.is_some() {
+        events |= ActiveEvents::CONTACT_FORCE_EVENTS;
+    }
+
+    let mut builder = ColliderBuilder::new(create_shape(&def.shape)?)
         .density(def.density)
-        .friction(def.friction)
-        .restitution(def.restitution)
-        .sensor(def.is_sensor);
+        .sensor(def.is_sensor)
+        .active_events(events)
+        .contact_force_event_threshold(def.contact_force_threshold.unwrap_or(0.0));
     
     // Add position/rotation offset if not at origin
     if def.position != Vector3::zeros() || def.rotation != Quaternion::identity() {
         builder = builder
-            .translation(to_na_vector(&def.position))
-            .rotation(to_na_vector(&def.rotation.to_euler_angles()));
+            .position(Isometry::from_parts(def.position.into(), def.rotation.into()));
     }
     
     // Add collision groups
-    if def.collision_groups != 0xFFFFFFFF {
-        let groups = InteractionGroups::new(
-            Group::from_bits(def.collision_groups as u32).unwrap_or(Group::all()),
-            Group::from_bits(def.collision_groups as u32).unwrap_or(Group::all())
-        );
-        builder = builder.collision_groups(groups);
+    builder = builder
+        .collision_groups(to_interaction_groups(&def.collision_groups))
+        .solver_groups(to_interaction_groups(&def.solver_groups));
+
+    let mut hooks = ActiveHooks::empty();
+    if def.filter_pairs {
+        hooks |= ActiveHooks::FILTER_CONTACT_PAIRS | ActiveHooks::FILTER_INTERSECTION_PAIR;
+    }
+    if def.modify_contacts {
+        hooks |= ActiveHooks::MODIFY_SOLVER_CONTACTS;
     }
     
-    builder
+    Ok(builder.active_hooks(hooks))
+}
+
+// Reads a collider back into a definition, Rapier's shape does not say which ColliderShape built it
+pub fn collider_def(collider: &Collider, shape: ColliderShape, material: MaterialId) -> ColliderDef {
+    let offset = collider.position_wrt_parent().copied().unwrap_or_else(Isometry::identity);
+    let events = collider.active_events();
+    let hooks = collider.active_hooks();
+
+    ColliderDef {
+        shape,
+        position: Vector3::from(offset.translation.vector),
+        rotation: Quaternion::from(offset.rotation),
+        density: collider.density(),
+        material,
+        is_sensor: collider.is_sensor(),
+        collision_groups: from_interaction_groups(collider.collision_groups()),
+        solver_groups: from_interaction_groups(collider.solver_groups()),
+        filter_pairs: hooks.contains(ActiveHooks::FILTER_CONTACT_PAIRS),
+        modify_contacts: hooks.contains(ActiveHooks::MODIFY_SOLVER_CONTACTS),
+        contact_force_threshold: events
+            .contains(ActiveEvents::CONTACT_FORCE_EVENTS)
+            .then(|| collider.contact_force_event_threshold()),
+    }
+}
+
+pub fn to_interaction_groups(groups: &CollisionGroups) -> InteractionGroups {
+    InteractionGroups::new(
+        Group::from_bits_truncate(groups.memberships),
+        Group::from_bits_truncate(groups.filter)
+    )
+}
+
+pub fn from_interaction_groups(groups: InteractionGroups) -> CollisionGroups {
+    CollisionGroups::new(groups.memberships.bits(), groups.filter.bits())
+}
+
+
+pub fn convert_joint_axis(axis: GpJointAxis) -> JointAxis {
+    match axis {
+        GpJointAxis::LinX => JointAxis::LinX,
+        GpJointAxis::LinY => JointAxis::LinY,
+        GpJointAxis::LinZ => JointAxis::LinZ,
+        GpJointAxis::AngX => JointAxis::AngX,
+        GpJointAxis::AngY => JointAxis::AngY,
+        GpJointAxis::AngZ => JointAxis::AngZ,
+    }
+}
+
+pub fn apply_joint_motor(joint: &mut GenericJoint, motor: &JointMotor) {
+    let axis = convert_joint_axis(motor.axis);
+
+    match motor.target {
+        MotorTarget::Velocity { velocity, factor } => {
+            joint.set_motor_velocity(axis, velocity, factor);
+        },
+        MotorTarget::Position { position, stiffness, damping } => {
+            joint.set_motor_position(axis, position, stiffness, damping);
+        },
+    }
+    joint.set_motor_max_force(axis, motor.max_force);
+}
+
+// Create a Rapier joint from our definition
+pub fn create_joint(def: &JointDef) -> GenericJoint {
+    let anchor_a = Point::from(def.local_anchor_a);
+    let anchor_b = Point::from(def.local_anchor_b);
+
+    let mut joint: GenericJoint = match &def.joint_type {
+        JointType::Fixed => {
+            FixedJointBuilder::new()
+                .local_anchor1(anchor_a)
+                .local_anchor2(anchor_b)
+                .into()
+        },
+        JointType::Revolute { axis } => {
+            RevoluteJointBuilder::new(UnitVector::new_normalize(Vector::from(*axis)))
+                .local_anchor1(anchor_a)
+                .local_anchor2(anchor_b)
+                .into()
+        },
+        JointType::Prismatic { axis } => {
+            PrismaticJointBuilder::new(UnitVector::new_normalize(Vector::from(*axis)))
+                .local_anchor1(anchor_a)
+                .local_anchor2(anchor_b)
+                .into()
+        },
+        JointType::Spherical => {
+            SphericalJointBuilder::new()
+                .local_anchor1(anchor_a)
+                .local_anchor2(anchor_b)
+                .into()
+        },
+        JointType::Rope { max_length } => {
+            RopeJointBuilder::new(*max_length)
+                .local_anchor1(anchor_a)
+                .local_anchor2(anchor_b)
+                .into()
+        },
+        JointType::Generic { locked_axes } => {
+            let mask = locked_axes.iter()
+                .fold(JointAxesMask::empty(), |mask, axis| mask | convert_joint_axis(*axis).into());
+
+            GenericJointBuilder::new(mask)
+                .local_anchor1(anchor_a)
+                .local_anchor2(anchor_b)
+                .build()
+        },
+    };
+
+    for limit in &def.limits {
+        joint.set_limits(convert_joint_axis(limit.axis), [limit.min, limit.max]);
+    }
+    for motor in &def.motors {
+        apply_joint_motor(&mut joint, motor);
+    }
+    joint.set_contacts_enabled(def.contacts_enabled);
+
+    joint
+}
+
+// Reads a joint back into a definition, limits and motors include changes made after add_joint
+pub(crate) fn joint_def(joint: &GenericJoint, info: &JointInfo) -> JointDef {
+    let axes = [GpJointAxis::LinX, GpJointAxis::LinY, GpJointAxis::LinZ, GpJointAxis::AngX, GpJointAxis::AngY, GpJointAxis::AngZ];
+
+    let limits = axes.iter()
+        .filter_map(|axis| {
+            let limits = joint.limits(convert_joint_axis(*axis))?;
+            Some(JointLimit { axis: *axis, min: limits.min, max: limits.max })
+        })
+        .collect();
+
+    let motors = axes.iter()
+        .filter_map(|axis| {
+            let motor = joint.motor(convert_joint_axis(*axis))?;
+            // velocity motors are position motors without stiffness
+            let target = if motor.stiffness == 0.0 {
+                MotorTarget::Velocity { velocity: motor.target_vel, factor: motor.damping }
+            } else {
+                MotorTarget::Position { position: motor.target_pos, stiffness: motor.stiffness, damping: motor.damping }
+            };
+            Some(JointMotor { axis: *axis, target, max_force: motor.max_force })
+        })
+        .collect();
+
+    JointDef {
+        joint_type: info.joint_type.clone(),
+        local_anchor_a: Vector3::from(joint.local_anchor1()),
+        local_anchor_b: Vector3::from(joint.local_anchor2()),
+        limits,
+        motors,
+        break_force: info.break_force,
+        break_torque: info.break_torque,
+        contacts_enabled: joint.contacts_enabled(),
+    }
+}
+
+pub fn create_character_controller(def: &CharacterControllerDef) -> KinematicCharacterController {
+    KinematicCharacterController {
+        up: UnitVector::new_normalize(Vector::from(def.up)),
+        offset: CharacterLength::Absolute(def.offset),
+        slide: def.slide,
+        autostep: def.step_height.map(|max_height| CharacterAutostep {
+            max_height: CharacterLength::Absolute(max_height),
+            min_width: CharacterLength::Absolute(def.step_min_width),
+            include_dynamic_bodies: false,
+        }),
+        max_slope_climb_angle: def.max_slope_climb_angle,
+        min_slope_slide_angle: def.min_slope_slide_angle,
+        snap_to_ground: def.snap_to_ground.map(CharacterLength::Absolute),
+        ..Default::default()
+    }
+}
+
+pub fn create_vehicle_controller(chassis: RigidBodyHandle, def: &VehicleDef) -> DynamicRayCastVehicleController {
+    let mut controller = DynamicRayCastVehicleController::new(chassis);
+    controller.index_up_axis = def.up_axis;
+    controller.index_forward_axis = def.forward_axis;
+
+    for wheel in &def.wheels {
+        let tuning = WheelTuning {
+            suspension_stiffness: wheel.suspension_stiffness,
+            suspension_compression: wheel.suspension_compression,
+            suspension_damping: wheel.suspension_damping,
+            max_suspension_travel: wheel.max_suspension_travel,
+            side_friction_stiffness: wheel.side_friction_stiffness,
+            friction_slip: wheel.friction_slip,
+            max_suspension_force: wheel.max_suspension_force,
+        };
+        controller.add_wheel(
+            Point::from(wheel.connection_point),
+            Vector::from(wheel.direction),
+            Vector::from(wheel.axle),
+            wheel.suspension_rest_length,
+            wheel.radius,
+            &tuning,
+        );
+    }
+
+    controller
+}
+
+// Rapier only resets its stage timers between steps while the counters are enabled
+pub fn create_pipeline() -> PhysicsPipeline {
+    let mut pipeline = PhysicsPipeline::new();
+    pipeline.counters.enable();
+    pipeline
+}
+
+pub fn simulation_settings(params: &IntegrationParameters) -> SimulationSettings {
+    SimulationSettings {
+        solver_iterations: params.num_solver_iterations.get(),
+        friction_iterations: params.num_additional_friction_iterations,
+        stabilization_iterations: params.num_internal_stabilization_iterations,
+        contact_natural_frequency: params.contact_natural_frequency,
+        contact_damping_ratio: params.contact_damping_ratio,
+        joint_natural_frequency: params.joint_natural_frequency,
+        joint_damping_ratio: params.joint_damping_ratio,
+        allowed_penetration: params.normalized_allowed_linear_error,
+        max_penetration_correction: params.normalized_max_corrective_velocity,
+        prediction_distance: params.normalized_prediction_distance,
+        length_unit: params.length_unit,
+        max_ccd_substeps: params.max_ccd_substeps,
+        min_ccd_dt: params.min_ccd_dt,
+    }
+}
+
+// Leaves dt alone, World sets it every substep
+pub fn apply_simulation_settings(params: &mut IntegrationParameters, settings: &SimulationSettings) {
+    params.num_solver_iterations = std::num::NonZeroUsize::new(settings.solver_iterations.max(1)).unwrap();
+    params.num_additional_friction_iterations = settings.friction_iterations;
+    params.num_internal_stabilization_iterations = settings.stabilization_iterations;
+    params.contact_natural_frequency = settings.contact_natural_frequency;
+    params.contact_damping_ratio = settings.contact_damping_ratio;
+    params.joint_natural_frequency = settings.joint_natural_frequency;
+    params.joint_damping_ratio = settings.joint_damping_ratio;
+    params.normalized_allowed_linear_error = settings.allowed_penetration;
+    params.normalized_max_corrective_velocity = settings.max_penetration_correction;
+    params.normalized_prediction_distance = settings.prediction_distance;
+    params.length_unit = settings.length_unit;
+    params.max_ccd_substeps = settings.max_ccd_substeps.max(1);
+    params.min_ccd_dt = settings.min_ccd_dt;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collider::CompoundPart;
+    use crate::World;
+
+    // Quarter and half turns around each axis plus a few mixed rotations, the conversion
+    // itself is swept by gamerplex-math's round-trip tests
+    fn rotations() -> Vec<Quaternion> {
+        let mut rotations = vec![Quaternion::identity()];
+        for axis in [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z(), Vector3::new(1.0, -2.0, 0.5).normalize()] {
+            for angle in [0.5, 1.0, 2.0, 3.0, 4.0] {
+                rotations.push(Quaternion::from_axis_angle(&axis, angle * std::f32::consts::FRAC_PI_2));
+            }
+        }
+        rotations
+    }
+
+    // angle_to treats q and -q as the same rotation
+    fn assert_same_rotation(actual: Rotation<f32>, expected: Rotation<f32>) {
+        assert!(actual.angle_to(&expected) < 1e-3, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn body_orientation_survives_the_round_trip() {
+        let mut world = World::new(Vector3::zeros());
+        let expected = rotations();
+
+        for (entity, rotation) in expected.iter().enumerate() {
+            world.add_rigid_body(entity as u32, &Body {
+                body_type: BodyType::Static,
+                position: Vector3::new(entity as f32, 0.0, 0.0),
+                rotation: *rotation,
+                ..Default::default()
+            });
+        }
+
+        for (entity, position, rotation) in world.synchronize_transforms() {
+            assert_eq!(position, Vector3::new(entity as f32, 0.0, 0.0));
+            assert_same_rotation(rotation.into(), expected[entity as usize].into());
+        }
+    }
+
+    #[test]
+    fn collider_offset_rotation_is_applied() {
+        let mut world = World::new(Vector3::zeros());
+        let rotations = rotations();
+
+        for (entity, (body_rotation, offset_rotation)) in rotations.iter().zip(rotations.iter().rev()).enumerate() {
+            let body = world.add_rigid_body(entity as u32, &Body { rotation: *body_rotation, ..Default::default() });
+            let collider = world.add_collider(entity as u32, body, &ColliderDef {
+                position: Vector3::new(1.0, 2.0, 3.0),
+                rotation: *offset_rotation,
+                ..Default::default()
+            }).unwrap();
+
+            let collider = &world.collider_set[collider.to_rapier_handle()];
+            let expected = Rotation::from(*body_rotation) * Rotation::from(*offset_rotation);
+            assert_same_rotation(*collider.rotation(), expected);
+
+            let expected_position = Rotation::from(*body_rotation) * Vector::new(1.0, 2.0, 3.0);
+            assert!((collider.translation() - expected_position).norm() < 1e-5);
+        }
+    }
+
+    fn is_invalid(shape: ColliderShape) -> bool {
+        matches!(create_shape(&shape), Err(PhysicsError::InvalidShape(_)))
+    }
+
+    fn part(shape: ColliderShape) -> CompoundPart {
+        CompoundPart { shape, position: Vector3::zeros(), rotation: Quaternion::identity() }
+    }
+
+    // Closed unit cube around the origin with outward facing triangles
+    fn cube_mesh() -> (Vec<Vector3>, Vec<[u32; 3]>) {
+        let vertices = (0..8)
+            .map(|i| Vector3::new((i & 1) as f32 - 0.5, ((i >> 1) & 1) as f32 - 0.5, ((i >> 2) & 1) as f32 - 0.5))
+            .collect();
+        let indices = vec![
+            [0, 2, 1], [1, 2, 3], [4, 5, 6], [5, 7, 6], // -z, +z
+            [0, 1, 4], [1, 5, 4], [2, 6, 3], [3, 6, 7], // -y, +y
+            [0, 4, 2], [2, 4, 6], [1, 3, 5], [3, 7, 5], // -x, +x
+        ];
+        (vertices, indices)
+    }
+
+    #[test]
+    fn compounds_reject_composite_parts() {
+        let sphere = ColliderShape::Sphere { radius: 0.5 };
+        assert!(create_shape(&ColliderShape::Compound { parts: vec![part(sphere.clone())] }).is_ok());
+        assert!(is_invalid(ColliderShape::Compound { parts: Vec::new() }));
+
+        let nested = ColliderShape::Compound { parts: vec![part(sphere.clone())] };
+        assert!(is_invalid(ColliderShape::Compound { parts: vec![part(sphere), part(nested)] }));
+
+        let (vertices, indices) = cube_mesh();
+        assert!(is_invalid(ColliderShape::Compound { parts: vec![part(ColliderShape::TriMesh { vertices, indices })] }));
+    }
+
+    #[test]
+    fn degenerate_convex_hulls_are_invalid() {
+        // all points on one line
+        let points = (0..5).map(|i| Vector3::new(i as f32, 0.0, 0.0)).collect();
+        assert!(is_invalid(ColliderShape::ConvexHull { points }));
+        assert!(is_invalid(ColliderShape::ConvexHull { points: Vec::new() }));
+
+        let (points, _) = cube_mesh();
+        assert!(create_shape(&ColliderShape::ConvexHull { points }).is_ok());
+    }
+
+    #[test]
+    fn heightfields_need_rows_times_columns_heights() {
+        let heightfield = |heights: Vec<f32>, rows, columns| ColliderShape::HeightField {
+            heights,
+            rows,
+            columns,
+            scale: Vector3::new(10.0, 1.0, 10.0),
+        };
+
+        assert!(create_shape(&heightfield(vec![0.0; 6], 2, 3)).is_ok());
+        assert!(is_invalid(heightfield(vec![0.0; 5], 2, 3)));
+        assert!(is_invalid(heightfield(vec![0.0; 7], 2, 3)));
+        assert!(is_invalid(heightfield(vec![0.0; 3], 1, 3)));
+        assert!(is_invalid(heightfield(Vec::new(), 0, 0)));
+    }
+
+    #[test]
+    fn convex_decompositions_collide() {
+        let (vertices, indices) = cube_mesh();
+        assert!(is_invalid(ColliderShape::ConvexDecomposition { vertices: vertices.clone(), indices: vec![[0, 1, 8]] }));
+
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+        let ground = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, ground, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(20.0, 0.5, 20.0) },
+            ..Default::default()
+        }).unwrap();
+
+        let body = world.add_rigid_body(1, &Body { position: Vector3::new(0.0, 2.0, 0.0), ..Default::default() });
+        world.add_collider(1, body, &ColliderDef {
+            shape: ColliderShape::ConvexDecomposition { vertices, indices },
+            ..Default::default()
+        }).unwrap();
+
+        for _ in 0..120 {
+            world.step(1.0 / 60.0);
+        }
+        assert!(world.is_touching(0, 1));
+        // the cube rests on the ground instead of falling through it
+        assert!((world.rigid_body(1).unwrap().translation().y - 1.0).abs() < 0.05);
+    }
 }
@@ -0,0 +1,307 @@
+use gamerplex_math::Vector3;
+use rapier3d::prelude::ImpulseJointHandle;
+use serde::{Deserialize, Serialize};
+
+use crate::integration::rapier;
+use crate::{EntityId, JointHandle, PhysicsError, World};
+
+// Degrees of freedom in the joint's local frame. Revolute joints rotate around
+// AngX and prismatic joints slide along LinX, so their limits and motors go there.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum JointAxis {
+    LinX,
+    LinY,
+    LinZ,
+    AngX,
+    AngY,
+    AngZ,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum JointType {
+    Fixed,                              // welds the two bodies together
+    Revolute { axis: Vector3 },         // hinge, e.g. doors and wheels
+    Prismatic { axis: Vector3 },        // slider along the axis
+    Spherical,                          // ball and socket, e.g. ragdoll shoulders
+    Rope { max_length: f32 },           // bodies can get closer but not further than max_length
+    Generic { locked_axes: Vec<JointAxis> }, // 6-DOF, every axis not listed is free
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JointLimit {
+    pub axis: JointAxis,
+    pub min: f32,  // radians for angular axes
+    pub max: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum MotorTarget {
+    Velocity { velocity: f32, factor: f32 },
+    Position { position: f32, stiffness: f32, damping: f32 },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JointMotor {
+    pub axis: JointAxis,
+    pub target: MotorTarget,
+    pub max_force: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JointDef {
+    pub joint_type: JointType,
+    pub local_anchor_a: Vector3,   // attachment point in body a's local space
+    pub local_anchor_b: Vector3,   // attachment point in body b's local space
+    pub limits: Vec<JointLimit>,
+    pub motors: Vec<JointMotor>,
+    pub break_force: Option<f32>,  // joint breaks when its linear force exceeds this
+    pub break_torque: Option<f32>, // joint breaks when its torque exceeds this
+    pub contacts_enabled: bool,    // whether the two bodies still collide with each other
+}
+
+impl Default for JointDef {
+    fn default() -> Self {
+        Self {
+            joint_type: JointType::Fixed,
+            local_anchor_a: Vector3::zeros(),
+            local_anchor_b: Vector3::zeros(),
+            limits: Vec::new(),
+            motors: Vec::new(),
+            break_force: None,
+            break_torque: None,
+            contacts_enabled: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct JointBrokenEvent {
+    pub joint: JointHandle,
+    pub entity_a: EntityId,
+    pub entity_b: EntityId,
+    pub force: f32,  // linear force at the moment the joint broke
+    pub torque: f32,
+}
+
+// Bookkeeping for joints created through add_joint
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct JointInfo {
+    pub entity_a: EntityId,
+    pub entity_b: EntityId,
+    pub break_force: Option<f32>,
+    pub break_torque: Option<f32>,
+    pub joint_type: JointType, // the rest of the definition can be read back from the Rapier joint
+}
+
+impl World {
+    pub fn add_joint(&mut self, entity_a: EntityId, entity_b: EntityId, def: &JointDef) -> Result<JointHandle, PhysicsError> {
+        let body_a = self.entity_body_map.get(&entity_a).ok_or(PhysicsError::NoRigidBody(entity_a))?.to_rapier_handle();
+        let body_b = self.entity_body_map.get(&entity_b).ok_or(PhysicsError::NoRigidBody(entity_b))?.to_rapier_handle();
+
+        let handle = self.impulse_joint_set.insert(body_a, body_b, rapier::create_joint(def), true);
+
+        self.joints.insert(handle, JointInfo {
+            entity_a,
+            entity_b,
+            break_force: def.break_force,
+            break_torque: def.break_torque,
+            joint_type: def.joint_type.clone(),
+        });
+
+        Ok(JointHandle::from_rapier_handle(handle))
+    }
+
+    pub fn remove_joint(&mut self, handle: JointHandle) -> Result<(), PhysicsError> {
+        let rapier_handle = handle.to_rapier_handle();
+        self.impulse_joint_set
+            .remove(rapier_handle, true)
+            .ok_or(PhysicsError::InvalidJointHandle(handle))?;
+        self.joints.remove(&rapier_handle);
+        Ok(())
+    }
+
+    pub fn joint_entities(&self, handle: JointHandle) -> Option<(EntityId, EntityId)> {
+        self.joints
+            .get(&handle.to_rapier_handle())
+            .map(|info| (info.entity_a, info.entity_b))
+    }
+
+    pub fn set_joint_motor(&mut self, handle: JointHandle, motor: &JointMotor) -> Result<(), PhysicsError> {
+        let joint = self.impulse_joint_set
+            .get_mut(handle.to_rapier_handle(), true)
+            .ok_or(PhysicsError::InvalidJointHandle(handle))?;
+        rapier::apply_joint_motor(&mut joint.data, motor);
+        Ok(())
+    }
+
+    // Stops the motor from driving the axis, the axis is left free (or limited)
+    pub fn disable_joint_motor(&mut self, handle: JointHandle, axis: JointAxis) -> Result<(), PhysicsError> {
+        self.set_joint_motor(handle, &JointMotor {
+            axis,
+            target: MotorTarget::Velocity { velocity: 0.0, factor: 0.0 },
+            max_force: 0.0,
+        })
+    }
+
+    pub fn set_joint_limit(&mut self, handle: JointHandle, limit: &JointLimit) -> Result<(), PhysicsError> {
+        let joint = self.impulse_joint_set
+            .get_mut(handle.to_rapier_handle(), true)
+            .ok_or(PhysicsError::InvalidJointHandle(handle))?;
+        joint.data.set_limits(rapier::convert_joint_axis(limit.axis), [limit.min, limit.max]);
+        Ok(())
+    }
+
+    pub fn joint_broken_events(&self) -> &[JointBrokenEvent] {
+        &self.joint_broken_events
+    }
+
+    // Joint impulses are only known after the solver ran, so this runs after every substep
+    pub(crate) fn break_overloaded_joints(&mut self, dt: f32) {
+        let mut broken = Vec::new();
+        // the solver keeps the impulse of its last internal substep, so dividing by that
+        // substep's length gives the force, see joints_break_under_heavy_loads
+        let substep_dt = dt / self.integration_parameters.num_solver_iterations.get() as f32;
+
+        for (handle, info) in &self.joints {
+            if info.break_force.is_none() && info.break_torque.is_none() {
+                continue;
+            }
+            let Some(joint) = self.impulse_joint_set.get(*handle) else {
+                continue;
+            };
+
+            // locked axes report through `impulses`, limited ones (e.g. ropes) through their limit
+            let mut impulses = joint.impulses;
+            for (i, limit) in joint.data.limits.iter().enumerate() {
+                impulses[i] += limit.impulse;
+            }
+
+            let force = impulses.fixed_rows::<3>(0).norm() / substep_dt;
+            let torque = impulses.fixed_rows::<3>(3).norm() / substep_dt;
+
+            let force_exceeded = info.break_force.is_some_and(|max| force > max);
+            let torque_exceeded = info.break_torque.is_some_and(|max| torque > max);
+
+            if force_exceeded || torque_exceeded {
+                broken.push((*handle, force, torque));
+            }
+        }
+
+        // removal order decides which arena slots get reused, keep it independent of hash map order
+        broken.sort_by_key(|(handle, _, _)| handle.into_raw_parts());
+        for (handle, force, torque) in broken {
+            self.impulse_joint_set.remove(handle, true);
+            if let Some(info) = self.joints.remove(&handle) {
+                self.joint_broken_events.push(JointBrokenEvent {
+                    joint: JointHandle::from_rapier_handle(handle),
+                    entity_a: info.entity_a,
+                    entity_b: info.entity_b,
+                    force,
+                    torque,
+                });
+            }
+        }
+    }
+
+    // Rapier drops the joints of a removed body on its own
+    pub(crate) fn forget_removed_joints(&mut self) {
+        let joint_set = &self.impulse_joint_set;
+        self.joints.retain(|handle: &ImpulseJointHandle, _| joint_set.contains(*handle));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, MassMode};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    // A static anchor at the origin and a 10 kg box hanging next to it, nothing joined yet
+    fn anchored_box(gravity: Vector3, position: Vector3) -> World {
+        let mut world = World::new(gravity);
+        world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        let body = world.add_rigid_body(1, &Body { position, mass: MassMode::Total(10.0), ..Default::default() });
+        world.add_collider(1, body, &ColliderDef::default()).unwrap();
+        world
+    }
+
+    #[test]
+    fn limits_keep_a_hinge_in_range() {
+        let mut world = anchored_box(Vector3::new(0.0, -9.81, 0.0), Vector3::new(2.0, 0.0, 0.0));
+        world.add_joint(0, 1, &JointDef {
+            joint_type: JointType::Revolute { axis: Vector3::unit_z() },
+            local_anchor_b: Vector3::new(-2.0, 0.0, 0.0),
+            limits: vec![JointLimit { axis: JointAxis::AngX, min: -0.5, max: 0.5 }],
+            ..Default::default()
+        }).unwrap();
+
+        let mut lowest = 0.0_f32;
+        for _ in 0..120 {
+            world.step(DT);
+            lowest = lowest.min(world.rigid_body(1).unwrap().translation().y);
+        }
+        // without the limit it would swing all the way down to -2
+        assert!(lowest > -2.0 * 0.5_f32.sin() - 0.05);
+        assert!(lowest < -0.5);
+    }
+
+    #[test]
+    fn velocity_motor_reaches_its_target() {
+        let mut world = anchored_box(Vector3::zeros(), Vector3::zeros());
+        let joint = world.add_joint(0, 1, &JointDef {
+            joint_type: JointType::Revolute { axis: Vector3::unit_y() },
+            ..Default::default()
+        }).unwrap();
+        world.set_joint_motor(joint, &JointMotor {
+            axis: JointAxis::AngX,
+            target: MotorTarget::Velocity { velocity: 2.0, factor: 50.0 },
+            max_force: 1000.0,
+        }).unwrap();
+
+        for _ in 0..60 {
+            world.step(DT);
+        }
+        assert!((world.angular_velocity(1).unwrap().y - 2.0).abs() < 0.05);
+
+        world.disable_joint_motor(joint, JointAxis::AngX).unwrap();
+        world.set_angular_velocity(1, Vector3::zeros()).unwrap();
+        world.step(DT);
+        assert!(world.angular_velocity(1).unwrap().y.abs() < 1e-3);
+    }
+
+    // Hangs the box from the anchor for a second, the load is about 98 N
+    fn hang(break_force: f32) -> World {
+        let mut world = anchored_box(Vector3::new(0.0, -9.81, 0.0), Vector3::new(0.0, -1.0, 0.0));
+        world.add_joint(0, 1, &JointDef {
+            joint_type: JointType::Spherical,
+            local_anchor_b: Vector3::new(0.0, 1.0, 0.0),
+            break_force: Some(break_force),
+            ..Default::default()
+        }).unwrap();
+
+        for _ in 0..60 {
+            world.step(DT);
+        }
+        world
+    }
+
+    #[test]
+    fn joints_break_under_heavy_loads() {
+        let world = hang(150.0);
+        assert!(world.joint_broken_events().is_empty());
+        assert_eq!(world.impulse_joint_set.len(), 1);
+        assert!(world.rigid_body(1).unwrap().translation().y > -1.1);
+
+        let world = hang(50.0);
+        let events = world.joint_broken_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].entity_a, events[0].entity_b), (0, 1));
+        // the estimate matches the weight it carried
+        assert!((events[0].force - 98.1).abs() < 10.0, "force {}", events[0].force);
+        assert_eq!(world.joint_entities(events[0].joint), None);
+        assert_eq!(world.impulse_joint_set.len(), 0);
+        assert!(world.rigid_body(1).unwrap().translation().y < -2.0);
+    }
+}
@@ -1,20 +1,49 @@
 pub use world::*;
 pub use body::*;
 pub use collider::*;
+pub use joint::*;
+pub use character::*;
 pub use events::*;
 pub use handles::*;
 pub use forces::*;
 pub use query::*;
+pub use filter::*;
+pub use material::*;
+pub use trigger::*;
+pub use vehicle::*;
+pub use stats::*;
+pub use settings::*;
+pub use scene::*;
+pub use sleep::*;
 pub use integration::*;
+pub use error::*;
+#[cfg(feature = "parallel")]
+pub use parallel::*;
 
 mod world;
 mod body;
 mod collider;
+mod joint;
+mod character;
 mod events;
 mod handles;
 mod forces;
 mod query;
+mod filter;
+mod material;
+mod trigger;
+mod vehicle;
+mod stats;
+mod settings;
+mod scene;
+mod sleep;
 mod integration;
+mod error;
+mod lifecycle;
+mod snapshot;
+mod buoyancy;
+#[cfg(feature = "parallel")]
+mod parallel;
 
 #[cfg(feature = "debug")]
 pub mod debug;
\ No newline at end of file
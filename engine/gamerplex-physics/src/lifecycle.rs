@@ -0,0 +1,249 @@
+use rapier3d::prelude::ColliderHandle as RapierColliderHandle;
+
+use crate::{BodyHandle, ColliderHandle, EntityId, PhysicsError, World};
+
+impl World {
+    // Removes the entity's rigid body, every collider attached to it and every
+    // collider registered under the entity, plus the joints attached to the body
+    pub fn remove_entity(&mut self, entity: EntityId) -> Result<(), PhysicsError> {
+        let body = self.entity_body_map.get(&entity).copied();
+        let colliders = self.entity_collider_map.get(&entity).cloned().unwrap_or_default();
+
+        if body.is_none() && colliders.is_empty() {
+            return Err(PhysicsError::NoRigidBody(entity));
+        }
+
+        if let Some(body) = body {
+            self.remove_rigid_body(body)?;
+        }
+
+        // colliders the entity owns on another entity's body
+        for collider in colliders {
+            if self.collider_set.contains(collider.to_rapier_handle()) {
+                self.remove_collider(collider)?;
+            }
+        }
+
+        self.entity_collider_map.remove(&entity);
+        Ok(())
+    }
+
+    // Removes the body together with its attached colliders and joints, and the
+    // character controller, trigger or force field of its entity
+    pub fn remove_rigid_body(&mut self, handle: BodyHandle) -> Result<(), PhysicsError> {
+        let rapier_handle = handle.to_rapier_handle();
+        let removed = self.rigid_body_set.remove(
+            rapier_handle,
+            &mut self.island_manager,
+            &mut self.collider_set,
+            &mut self.impulse_joint_set,
+            &mut self.multibody_joint_set,
+            true,
+        ).ok_or(PhysicsError::InvalidBodyHandle(handle))?;
+
+        for collider in removed.colliders() {
+            self.forget_collider(*collider);
+        }
+
+        self.previous_poses.remove(&rapier_handle);
+        self.mass_modes.remove(&rapier_handle);
+        self.vehicles.remove(&rapier_handle);
+        self.sleeping_bodies.remove(&rapier_handle);
+        if let Some(entity) = self.body_entity_map.remove(&rapier_handle) {
+            self.entity_body_map.remove(&entity);
+            self.character_controllers.remove(&entity);
+            self.remove_trigger(entity);
+            self.remove_force_field(entity);
+        }
+        self.forget_removed_joints();
+
+        Ok(())
+    }
+
+    pub fn remove_collider(&mut self, handle: ColliderHandle) -> Result<(), PhysicsError> {
+        let removed = self.collider_set.remove(
+            handle.to_rapier_handle(),
+            &mut self.island_manager,
+            &mut self.rigid_body_set,
+            true,
+        ).ok_or(PhysicsError::InvalidColliderHandle(handle))?;
+
+        if let Some(body) = removed.parent() {
+            self.refresh_mass_properties(body);
+        }
+        self.forget_collider(handle.to_rapier_handle());
+        Ok(())
+    }
+
+    // A disabled body keeps its state but is taken out of the simulation, its colliders
+    // stop colliding and stop showing up in queries until it is enabled again
+    pub fn set_body_enabled(&mut self, entity: EntityId, enabled: bool) -> Result<(), PhysicsError> {
+        let body = self.rigid_body_mut(entity)?;
+        body.set_enabled(enabled);
+        Ok(())
+    }
+
+    pub fn is_body_enabled(&self, entity: EntityId) -> Result<bool, PhysicsError> {
+        Ok(self.rigid_body(entity)?.is_enabled())
+    }
+
+    fn forget_collider(&mut self, collider: RapierColliderHandle) {
+        self.collider_shapes.remove(&collider);
+        self.custom_mass_densities.remove(&collider);
+        let Some(entity) = self.collider_entity_map.remove(&collider) else {
+            return;
+        };
+
+        let collider = ColliderHandle::from_rapier_handle(collider);
+        if let Some(colliders) = self.entity_collider_map.get_mut(&entity) {
+            colliders.retain(|handle| *handle != collider);
+            if colliders.is_empty() {
+                self.entity_collider_map.remove(&entity);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, ColliderShape, ForceFieldDef, JointDef, QueryFilter, TriggerDef};
+    use gamerplex_math::Vector3;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    // Ground, a box resting on it with a second collider owned by entity 3, and box 2
+    // jointed to box 1
+    fn resting_boxes() -> World {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+        let ground = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, ground, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(20.0, 0.5, 20.0) },
+            ..Default::default()
+        }).unwrap();
+
+        let body = world.add_rigid_body(1, &Body { position: Vector3::new(0.0, 1.0, 0.0), ..Default::default() });
+        world.add_collider(1, body, &ColliderDef::default()).unwrap();
+        world.add_collider(3, body, &ColliderDef { position: Vector3::new(0.0, 1.0, 0.0), ..Default::default() }).unwrap();
+
+        let body = world.add_rigid_body(2, &Body { position: Vector3::new(3.0, 1.0, 0.0), ..Default::default() });
+        world.add_collider(2, body, &ColliderDef::default()).unwrap();
+        world.add_joint(1, 2, &JointDef { local_anchor_b: Vector3::new(-3.0, 0.0, 0.0), ..Default::default() }).unwrap();
+
+        for _ in 0..30 {
+            world.step(DT);
+        }
+        world
+    }
+
+    fn events_with(world: &World, entity: EntityId) -> usize {
+        world.collision_events()
+            .iter()
+            .filter(|event| event.entity_a == entity || event.entity_b == entity)
+            .count()
+    }
+
+    #[test]
+    fn removing_an_entity_leaves_nothing_behind() {
+        let mut world = resting_boxes();
+        assert!(world.is_touching(0, 1));
+        let body = world.body_handle(1).unwrap();
+
+        world.remove_entity(1).unwrap();
+        assert_eq!(world.body_handle(1), None);
+        assert_eq!(world.body_entity(body), None);
+        assert!(world.colliders(1).is_empty());
+        // entity 3's collider went away with the body it was attached to
+        assert!(world.colliders(3).is_empty());
+        assert!(!world.entity_collider_map.contains_key(&3));
+        assert_eq!(world.collider_entity_map.len(), 2);
+        assert!(world.joints.is_empty());
+        assert_eq!(world.collider_set.len(), 2);
+
+        world.step(DT);
+        world.clear_events();
+        for _ in 0..10 {
+            world.step(DT);
+        }
+        assert_eq!(events_with(&world, 1), 0);
+        assert_eq!(events_with(&world, 3), 0);
+        assert!(!world.is_touching(0, 1));
+
+        assert_eq!(world.remove_entity(1), Err(PhysicsError::NoRigidBody(1)));
+        assert_eq!(world.remove_rigid_body(body), Err(PhysicsError::InvalidBodyHandle(body)));
+    }
+
+    #[test]
+    fn removing_colliders_one_by_one() {
+        let mut world = resting_boxes();
+        let colliders = world.colliders(1).to_vec();
+        let extra = world.colliders(3)[0];
+
+        world.remove_collider(extra).unwrap();
+        assert!(!world.entity_collider_map.contains_key(&3));
+        assert_eq!(world.colliders(1), colliders.as_slice());
+        assert_eq!(world.remove_collider(extra), Err(PhysicsError::InvalidColliderHandle(extra)));
+
+        world.remove_collider(colliders[0]).unwrap();
+        assert!(!world.entity_collider_map.contains_key(&1));
+        assert_eq!(world.collider_entity_map.len(), 2);
+        // the body stays without colliders
+        assert!(world.body_handle(1).is_some());
+    }
+
+    #[test]
+    fn removing_a_body_drops_its_trigger() {
+        let mut world = World::new(Vector3::zeros());
+        let sensor = ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(2.0, 2.0, 2.0) },
+            is_sensor: true,
+            ..Default::default()
+        };
+        let zone = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, zone, &sensor).unwrap();
+        let body = world.add_rigid_body(1, &Body::default());
+        world.add_collider(1, body, &ColliderDef::default()).unwrap();
+        world.step(DT);
+        world.add_trigger(0, &TriggerDef::default()).unwrap();
+        world.add_force_field(0, &ForceFieldDef::default()).unwrap();
+        assert_eq!(world.entities_in_trigger(0), vec![1]);
+
+        world.remove_rigid_body(zone).unwrap();
+        assert!(world.triggers.is_empty());
+        assert!(world.trigger_pairs.is_empty());
+        assert!(world.force_fields.is_empty());
+
+        // a new body for the entity starts out as a plain sensor
+        let zone = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, zone, &sensor).unwrap();
+        world.clear_events();
+        world.step(DT);
+        world.step(DT);
+        assert!(world.entities_in_trigger(0).is_empty());
+        assert!(world.trigger_events().is_empty());
+    }
+
+    #[test]
+    fn disabled_bodies_stop_simulating_and_colliding() {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+        let body = world.add_rigid_body(1, &Body { position: Vector3::new(0.0, 5.0, 0.0), ..Default::default() });
+        world.add_collider(1, body, &ColliderDef::default()).unwrap();
+
+        world.set_body_enabled(1, false).unwrap();
+        assert!(!world.is_body_enabled(1).unwrap());
+        for _ in 0..10 {
+            world.step(DT);
+        }
+        assert_eq!(world.rigid_body(1).unwrap().translation().y, 5.0);
+        assert!(world.raycast(Vector3::zeros(), Vector3::unit_y(), 10.0, &QueryFilter::default()).is_none());
+
+        world.set_body_enabled(1, true).unwrap();
+        assert!(world.is_body_enabled(1).unwrap());
+        world.step(DT);
+        assert!(world.rigid_body(1).unwrap().translation().y < 5.0);
+        assert_eq!(world.raycast(Vector3::zeros(), Vector3::unit_y(), 10.0, &QueryFilter::default()).map(|hit| hit.entity), Some(1));
+
+        assert_eq!(world.set_body_enabled(7, false), Err(PhysicsError::NoRigidBody(7)));
+        assert_eq!(world.is_body_enabled(7), Err(PhysicsError::NoRigidBody(7)));
+    }
+}
@@ -0,0 +1,247 @@
+use rapier3d::prelude::{CoefficientCombineRule, ColliderHandle as RapierColliderHandle};
+use serde::{Deserialize, Serialize};
+
+use crate::{ColliderHandle, PhysicsError, World};
+
+// Index into the world's material registry, stored in each collider's user data
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MaterialId(u32);
+
+impl MaterialId {
+    // Materials every world starts with
+    pub const DEFAULT: MaterialId = MaterialId(0);
+    pub const ICE: MaterialId = MaterialId(1);
+    pub const RUBBER: MaterialId = MaterialId(2);
+    pub const METAL: MaterialId = MaterialId(3);
+    pub const WOOD: MaterialId = MaterialId(4);
+
+    pub fn index(&self) -> u32 {
+        self.0
+    }
+}
+
+// How the coefficients of two touching colliders are merged. When the two
+// materials disagree the rule further down this list wins.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CombineRule {
+    Average,
+    Min,
+    Multiply,
+    Max,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhysicsMaterial {
+    pub friction: f32,    // 0.0 to 1.0 -> standard
+    pub restitution: f32, // bounciness, 0.0 to 1.0
+    pub friction_combine: CombineRule,
+    pub restitution_combine: CombineRule,
+    pub surface: u32,     // user-defined surface type, e.g. an index into the footstep sound table. The built-in materials use 1 to 4
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        Self {
+            friction: 0.5,
+            restitution: 0.0,
+            friction_combine: CombineRule::Average,
+            restitution_combine: CombineRule::Average,
+            surface: 0,
+        }
+    }
+}
+
+impl PhysicsMaterial {
+    pub fn ice() -> Self {
+        Self {
+            friction: 0.02,
+            restitution: 0.05,
+            surface: 1,
+            // ice stays slippery whatever stands on it
+            friction_combine: CombineRule::Min,
+            ..Default::default()
+        }
+    }
+
+    pub fn rubber() -> Self {
+        Self {
+            friction: 0.9,
+            restitution: 0.8,
+            surface: 2,
+            restitution_combine: CombineRule::Max,
+            ..Default::default()
+        }
+    }
+
+    pub fn metal() -> Self {
+        Self {
+            friction: 0.4,
+            restitution: 0.2,
+            surface: 3,
+            ..Default::default()
+        }
+    }
+
+    pub fn wood() -> Self {
+        Self {
+            friction: 0.6,
+            restitution: 0.3,
+            surface: 4,
+            ..Default::default()
+        }
+    }
+}
+
+pub(crate) fn default_materials() -> Vec<(String, PhysicsMaterial)> {
+    vec![
+        ("default".to_string(), PhysicsMaterial::default()),
+        ("ice".to_string(), PhysicsMaterial::ice()),
+        ("rubber".to_string(), PhysicsMaterial::rubber()),
+        ("metal".to_string(), PhysicsMaterial::metal()),
+        ("wood".to_string(), PhysicsMaterial::wood()),
+    ]
+}
+
+impl World {
+    // Registering an existing name replaces that material, colliders using it pick up the change
+    pub fn register_material(&mut self, name: &str, material: PhysicsMaterial) -> MaterialId {
+        if let Some(id) = self.material_id(name) {
+            self.update_material(id, material);
+            return id;
+        }
+
+        self.materials.push((name.to_string(), material));
+        MaterialId(self.materials.len() as u32 - 1)
+    }
+
+    pub fn material_id(&self, name: &str) -> Option<MaterialId> {
+        self.materials
+            .iter()
+            .position(|(material_name, _)| material_name == name)
+            .map(|index| MaterialId(index as u32))
+    }
+
+    pub fn material(&self, id: MaterialId) -> Result<&PhysicsMaterial, PhysicsError> {
+        self.materials
+            .get(id.0 as usize)
+            .map(|(_, material)| material)
+            .ok_or(PhysicsError::UnknownMaterial(id))
+    }
+
+    pub fn set_collider_material(&mut self, handle: ColliderHandle, id: MaterialId) -> Result<(), PhysicsError> {
+        let material = self.material(id)?.clone();
+        let collider = self.collider_set
+            .get_mut(handle.to_rapier_handle())
+            .ok_or(PhysicsError::InvalidColliderHandle(handle))?;
+
+        apply_material(collider, id, &material);
+        Ok(())
+    }
+
+    pub fn collider_material(&self, handle: ColliderHandle) -> Result<MaterialId, PhysicsError> {
+        self.collider_set
+            .get(handle.to_rapier_handle())
+            .map(|collider| MaterialId(collider.user_data as u32))
+            .ok_or(PhysicsError::InvalidColliderHandle(handle))
+    }
+
+    // Surface tag of the collider's material, 0 for colliders we do not know
+    pub(crate) fn collider_surface(&self, collider: RapierColliderHandle) -> u32 {
+        self.collider_set
+            .get(collider)
+            .and_then(|collider| self.materials.get(collider.user_data as usize))
+            .map_or(0, |(_, material)| material.surface)
+    }
+
+    fn update_material(&mut self, id: MaterialId, material: PhysicsMaterial) {
+        for (_, collider) in self.collider_set.iter_mut() {
+            if collider.user_data == id.0 as u128 {
+                apply_material(collider, id, &material);
+            }
+        }
+        self.materials[id.0 as usize].1 = material;
+    }
+}
+
+pub(crate) fn apply_material(collider: &mut rapier3d::prelude::Collider, id: MaterialId, material: &PhysicsMaterial) {
+    collider.user_data = id.0 as u128;
+    collider.set_friction(material.friction);
+    collider.set_restitution(material.restitution);
+    collider.set_friction_combine_rule(convert_combine_rule(material.friction_combine));
+    collider.set_restitution_combine_rule(convert_combine_rule(material.restitution_combine));
+}
+
+fn convert_combine_rule(rule: CombineRule) -> CoefficientCombineRule {
+    match rule {
+        CombineRule::Average => CoefficientCombineRule::Average,
+        CombineRule::Min => CoefficientCombineRule::Min,
+        CombineRule::Multiply => CoefficientCombineRule::Multiply,
+        CombineRule::Max => CoefficientCombineRule::Max,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, ColliderShape, QueryFilter};
+    use gamerplex_math::Vector3;
+
+    // A box pushed sideways along a floor, returns how far it slid
+    fn slide(floor: MaterialId, block: MaterialId) -> f32 {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+
+        let ground = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, ground, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(50.0, 0.5, 50.0) },
+            material: floor,
+            ..Default::default()
+        }).unwrap();
+
+        let body = world.add_rigid_body(1, &Body {
+            position: Vector3::new(0.0, 1.0, 0.0),
+            linear_velocity: Vector3::new(5.0, 0.0, 0.0),
+            ..Default::default()
+        });
+        world.add_collider(1, body, &ColliderDef { material: block, ..Default::default() }).unwrap();
+
+        for _ in 0..60 {
+            world.step(1.0 / 60.0);
+        }
+        world.rigid_body(1).unwrap().translation().x
+    }
+
+    #[test]
+    fn min_combine_keeps_ice_slippery() {
+        let on_ice = slide(MaterialId::ICE, MaterialId::RUBBER);
+        let on_wood = slide(MaterialId::WOOD, MaterialId::RUBBER);
+        assert!(on_ice > on_wood * 2.0, "ice {} wood {}", on_ice, on_wood);
+    }
+
+    #[test]
+    fn materials_update_colliders_and_tag_hits() {
+        let mut world = World::new(Vector3::zeros());
+        let mud = world.register_material("mud", PhysicsMaterial { friction: 1.0, surface: 7, ..Default::default() });
+        assert_eq!(world.material_id("mud"), Some(mud));
+        assert_eq!(world.material_id("ice"), Some(MaterialId::ICE));
+
+        let body = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        let collider = world.add_collider(0, body, &ColliderDef { material: mud, ..Default::default() }).unwrap();
+        world.step(1.0 / 60.0);
+
+        let hit = world.raycast(Vector3::new(0.0, 5.0, 0.0), Vector3::new(0.0, -1.0, 0.0), 10.0, &QueryFilter::default()).unwrap();
+        assert_eq!(hit.surface, 7);
+
+        world.register_material("mud", PhysicsMaterial { friction: 0.3, surface: 7, ..Default::default() });
+        assert_eq!(world.collider_set[collider.to_rapier_handle()].friction(), 0.3);
+
+        world.set_collider_material(collider, MaterialId::METAL).unwrap();
+        assert_eq!(world.collider_material(collider), Ok(MaterialId::METAL));
+
+        let unknown = MaterialId(99);
+        assert!(matches!(
+            world.add_collider(0, body, &ColliderDef { material: unknown, ..Default::default() }),
+            Err(PhysicsError::UnknownMaterial(_))
+        ));
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
+use crate::{PhysicsError, World};
+
+// Pools can be shared between worlds, e.g. one pool per server process
+pub fn build_thread_pool(threads: usize) -> Result<Arc<ThreadPool>, PhysicsError> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|index| format!("gamerplex-physics-{}", index))
+        .build()
+        .map(Arc::new)
+        .map_err(|err| PhysicsError::ThreadPool(err.to_string()))
+}
+
+// Steps independent worlds concurrently, e.g. the matches hosted by one server.
+// Runs on the pool of the caller, wrap it in ThreadPool::install to pick one.
+pub fn step_worlds(worlds: &mut [World], delta_time: f32) {
+    worlds.par_iter_mut().for_each(|world| world.step(delta_time));
+}
+
+impl World {
+    // Pool the solver runs its islands on, None uses rayon's global pool
+    pub fn set_thread_pool(&mut self, pool: Option<Arc<ThreadPool>>) {
+        self.thread_pool = pool;
+    }
+
+    pub fn thread_pool(&self) -> Option<&Arc<ThreadPool>> {
+        self.thread_pool.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, ColliderDef};
+    use gamerplex_math::Vector3;
+
+    fn falling_boxes(count: u32) -> World {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+        for entity in 0..count {
+            let body = world.add_rigid_body(entity, &Body {
+                position: Vector3::new(0.0, entity as f32 * 1.1, 0.0),
+                ..Default::default()
+            });
+            world.add_collider(entity, body, &ColliderDef::default()).unwrap();
+        }
+        world
+    }
+
+    #[test]
+    fn concurrent_worlds_match_sequential_ones() {
+        let pool = build_thread_pool(2).unwrap();
+        let mut concurrent: Vec<World> = (1..=4).map(falling_boxes).collect();
+        let mut sequential: Vec<World> = (1..=4).map(falling_boxes).collect();
+        concurrent[0].set_thread_pool(Some(pool.clone()));
+
+        for _ in 0..60 {
+            pool.install(|| step_worlds(&mut concurrent, 1.0 / 60.0));
+            for world in sequential.iter_mut() {
+                world.step(1.0 / 60.0);
+            }
+        }
+
+        for (a, b) in concurrent.iter().zip(&sequential) {
+            assert_eq!(a.state_checksum(), b.state_checksum());
+        }
+    }
+}
@@ -1,33 +1,424 @@
-use gamerplex_math::Vector3;
+use gamerplex_math::{Quaternion, Vector3};
+use rapier3d::prelude::{
+    ColliderHandle as RapierColliderHandle,
+    Group,
+    InteractionGroups,
+    Isometry,
+    Point,
+    QueryFilter as RapierQueryFilter,
+    Ray,
+    SharedShape,
+    Vector,
+};
+use rapier3d::parry::query::ShapeCastOptions;
 
-use crate::{EntityId, World};
+use crate::collider::ColliderShape;
+use crate::integration::rapier;
+use crate::{EntityId, PhysicsError, World};
 
+#[derive(Clone, Debug)]
 pub struct RaycastResult {
     pub entity: EntityId,
     pub distance: f32,       // Distance from ray origin
-    pub point: Vector3,      // World space hit point 
+    pub point: Vector3,      // World space hit point
     pub normal: Vector3,     // Surface normal at hit point
+    pub surface: u32,        // Surface tag of the hit collider's material
 }
 
+#[derive(Clone, Debug)]
+pub struct ShapeCastResult {
+    pub entity: EntityId,
+    pub distance: f32,       // Distance travelled by the shape before the hit
+    pub point: Vector3,      // World space hit point on the other collider
+    pub normal: Vector3,     // Surface normal of the other collider at the hit point
+    pub surface: u32,        // Surface tag of the other collider's material
+}
+
+#[derive(Clone, Debug)]
+pub struct PointProjectionResult {
+    pub entity: EntityId,
+    pub point: Vector3,      // Closest point on the collider surface
+    pub is_inside: bool,     // The projected point was inside the collider
+}
+
+// Which colliders a scene query is allowed to hit
+#[derive(Clone, Debug, Default)]
+pub struct QueryFilter {
+    pub filter_groups: Option<u32>,      // only hit colliders that are members of these groups
+    pub exclude_sensors: bool,
+    pub exclude_entity: Option<EntityId>, // e.g. the character doing the query
+}
+
+impl QueryFilter {
+    pub fn groups(filter_groups: u32) -> Self {
+        Self {
+            filter_groups: Some(filter_groups),
+            ..Default::default()
+        }
+    }
+}
+
+// A zero or non-finite direction would send a NaN ray into rapier, such casts hit nothing
+fn cast_direction(direction: Vector3) -> Option<Vector<f32>> {
+    let length = direction.length();
+    (length.is_finite() && length > f32::EPSILON).then(|| Vector::from(direction) / length)
+}
+
+// Queries run against the query pipeline, which is refreshed at the end of every
+// simulation step. Call `update_query_pipeline` to see colliders added since then.
 impl World {
     pub fn raycast(
-        &self, 
-        origin: Vector3, 
-        direction: Vector3, 
+        &self,
+        origin: Vector3,
+        direction: Vector3,
         max_distance: f32,
-        filter_groups: Option<u32>
+        filter: &QueryFilter
     ) -> Option<RaycastResult> {
-        // Will be implemented when integrated with Rapier
-        None
+        let ray = Ray::new(Point::from(origin), cast_direction(direction)?);
+
+        self.query_pipeline.cast_ray_and_get_normal(
+            &self.rigid_body_set,
+            &self.collider_set,
+            &ray,
+            max_distance,
+            true,
+            self.rapier_query_filter(filter),
+        ).and_then(|(collider, hit)| Some(RaycastResult {
+            entity: self.collider_entity(collider)?,
+            distance: hit.time_of_impact,
+            point: Vector3::from(ray.point_at(hit.time_of_impact)),
+            normal: Vector3::from(hit.normal),
+            surface: self.collider_surface(collider),
+        }))
+    }
+
+    // Every hit along the ray, closest first
+    pub fn raycast_all(
+        &self,
+        origin: Vector3,
+        direction: Vector3,
+        max_distance: f32,
+        filter: &QueryFilter
+    ) -> Vec<RaycastResult> {
+        let Some(direction_vector) = cast_direction(direction) else {
+            return Vec::new();
+        };
+        let ray = Ray::new(Point::from(origin), direction_vector);
+        let mut hits = Vec::new();
+
+        self.query_pipeline.intersections_with_ray(
+            &self.rigid_body_set,
+            &self.collider_set,
+            &ray,
+            max_distance,
+            true,
+            self.rapier_query_filter(filter),
+            |collider, hit| {
+                if let Some(entity) = self.collider_entity(collider) {
+                    hits.push(RaycastResult {
+                        entity,
+                        distance: hit.time_of_impact,
+                        point: Vector3::from(ray.point_at(hit.time_of_impact)),
+                        normal: Vector3::from(hit.normal),
+                        surface: self.collider_surface(collider),
+                    });
+                }
+                true
+            },
+        );
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    // Sweeps `shape` from `position` along `direction`, e.g. a sphere sweep for character movement
+    pub fn shape_cast(
+        &self,
+        shape: &ColliderShape,
+        position: Vector3,
+        rotation: Quaternion,
+        direction: Vector3,
+        max_distance: f32,
+        filter: &QueryFilter
+    ) -> Result<Option<ShapeCastResult>, PhysicsError> {
+        let shape = rapier::create_shape(shape)?;
+        let Some(direction) = cast_direction(direction) else {
+            return Ok(None);
+        };
+        let options = ShapeCastOptions {
+            max_time_of_impact: max_distance,
+            target_distance: 0.0,
+            stop_at_penetration: true,
+            compute_impact_geometry_on_penetration: true,
+        };
+
+        let hit = self.query_pipeline.cast_shape(
+            &self.rigid_body_set,
+            &self.collider_set,
+            &Isometry::from_parts(position.into(), rotation.into()),
+            &direction,
+            &*shape,
+            options,
+            self.rapier_query_filter(filter),
+        );
+
+        Ok(hit.and_then(|(collider, hit)| {
+            Some(ShapeCastResult {
+                entity: self.collider_entity(collider)?,
+                distance: hit.time_of_impact,
+                point: Vector3::from(hit.witness1),
+                normal: Vector3::from(hit.normal1.into_inner()),
+                surface: self.collider_surface(collider),
+            })
+        }))
     }
-    
+
     pub fn overlap_sphere(
-        &self, 
-        center: Vector3, 
+        &self,
+        center: Vector3,
+        radius: f32,
+        filter: &QueryFilter
+    ) -> Vec<EntityId> {
+        self.overlap_shared_shape(&SharedShape::ball(radius), center, Quaternion::identity(), filter)
+    }
+
+    pub fn overlap_box(
+        &self,
+        center: Vector3,
+        half_extents: Vector3,
+        rotation: Quaternion,
+        filter: &QueryFilter
+    ) -> Vec<EntityId> {
+        let shape = SharedShape::cuboid(half_extents.x, half_extents.y, half_extents.z);
+        self.overlap_shared_shape(&shape, center, rotation, filter)
+    }
+
+    pub fn overlap_capsule(
+        &self,
+        center: Vector3,
+        height: f32,
         radius: f32,
-        filter_groups: Option<u32>
+        rotation: Quaternion,
+        filter: &QueryFilter
     ) -> Vec<EntityId> {
-        // Will be implemented when integrated with Rapier
-        Vec::new()
+        let shape = SharedShape::capsule_y(height / 2.0, radius);
+        self.overlap_shared_shape(&shape, center, rotation, filter)
+    }
+
+    pub fn overlap_shape(
+        &self,
+        shape: &ColliderShape,
+        position: Vector3,
+        rotation: Quaternion,
+        filter: &QueryFilter
+    ) -> Result<Vec<EntityId>, PhysicsError> {
+        Ok(self.overlap_shared_shape(&rapier::create_shape(shape)?, position, rotation, filter))
+    }
+
+    // Closest point on the closest collider; points inside a collider project onto themselves
+    pub fn project_point(&self, point: Vector3, filter: &QueryFilter) -> Option<PointProjectionResult> {
+        let (collider, projection) = self.query_pipeline.project_point(
+            &self.rigid_body_set,
+            &self.collider_set,
+            &Point::from(point),
+            true,
+            self.rapier_query_filter(filter),
+        )?;
+
+        Some(PointProjectionResult {
+            entity: self.collider_entity(collider)?,
+            point: Vector3::from(projection.point),
+            is_inside: projection.is_inside,
+        })
     }
-}
\ No newline at end of file
+
+    pub fn entities_containing_point(&self, point: Vector3, filter: &QueryFilter) -> Vec<EntityId> {
+        let mut entities = Vec::new();
+
+        self.query_pipeline.intersections_with_point(
+            &self.rigid_body_set,
+            &self.collider_set,
+            &Point::from(point),
+            self.rapier_query_filter(filter),
+            |collider| {
+                entities.extend(self.collider_entity(collider));
+                true
+            },
+        );
+
+        entities.sort_unstable();
+        entities.dedup();
+        entities
+    }
+
+    pub fn contains_point(&self, point: Vector3, filter: &QueryFilter) -> bool {
+        !self.entities_containing_point(point, filter).is_empty()
+    }
+
+    // Queries otherwise only see colliders as of the last simulation step
+    pub fn update_query_pipeline(&mut self) {
+        self.query_pipeline.update(&self.collider_set);
+    }
+
+    fn overlap_shared_shape(
+        &self,
+        shape: &SharedShape,
+        position: Vector3,
+        rotation: Quaternion,
+        filter: &QueryFilter
+    ) -> Vec<EntityId> {
+        let mut entities = Vec::new();
+
+        self.query_pipeline.intersections_with_shape(
+            &self.rigid_body_set,
+            &self.collider_set,
+            &Isometry::from_parts(position.into(), rotation.into()),
+            &**shape,
+            self.rapier_query_filter(filter),
+            |collider: RapierColliderHandle| {
+                entities.extend(self.collider_entity(collider));
+                true
+            },
+        );
+
+        // an entity with several colliders is reported once
+        entities.sort_unstable();
+        entities.dedup();
+        entities
+    }
+
+    fn rapier_query_filter(&self, filter: &QueryFilter) -> RapierQueryFilter<'static> {
+        // rapier keeps the colliders of disabled bodies in the query pipeline
+        let mut rapier_filter = RapierQueryFilter::new().predicate(&|_, collider| collider.is_enabled());
+
+        if let Some(groups) = filter.filter_groups {
+            rapier_filter = rapier_filter.groups(InteractionGroups::new(
+                Group::all(),
+                Group::from_bits_truncate(groups),
+            ));
+        }
+
+        if filter.exclude_sensors {
+            rapier_filter = rapier_filter.exclude_sensors();
+        }
+
+        if let Some(body) = filter.exclude_entity.and_then(|entity| self.entity_body_map.get(&entity)) {
+            rapier_filter = rapier_filter.exclude_rigid_body(body.to_rapier_handle());
+        }
+
+        rapier_filter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, CollisionGroups};
+
+    // Ground 0 with its top at y = 0, unit boxes 1 and 2 stacked above it at y = 2 and 5,
+    // a sensor 3 at x = 5 and box 4 at x = 10 that is only in group 2
+    fn query_world() -> World {
+        let mut world = World::new(Vector3::zeros());
+        let boxes = [
+            (0, Vector3::new(0.0, -0.5, 0.0), Vector3::new(20.0, 0.5, 20.0), false, CollisionGroups::all()),
+            (1, Vector3::new(0.0, 2.0, 0.0), Vector3::new(0.5, 0.5, 0.5), false, CollisionGroups::all()),
+            (2, Vector3::new(0.0, 5.0, 0.0), Vector3::new(0.5, 0.5, 0.5), false, CollisionGroups::all()),
+            (3, Vector3::new(5.0, 1.0, 0.0), Vector3::new(1.0, 1.0, 1.0), true, CollisionGroups::all()),
+            (4, Vector3::new(10.0, 1.0, 0.0), Vector3::new(0.5, 0.5, 0.5), false, CollisionGroups::new(2, u32::MAX)),
+        ];
+        for (entity, position, half_extents, is_sensor, collision_groups) in boxes {
+            let body = world.add_rigid_body(entity, &Body { body_type: BodyType::Static, position, ..Default::default() });
+            world.add_collider(entity, body, &ColliderDef {
+                shape: ColliderShape::Box { half_extents },
+                is_sensor,
+                collision_groups,
+                ..Default::default()
+            }).unwrap();
+        }
+        world.update_query_pipeline();
+        world
+    }
+
+    #[test]
+    fn rays_hit_closest_first() {
+        let world = query_world();
+        let down = -Vector3::unit_y();
+        let hit = world.raycast(Vector3::new(0.0, 10.0, 0.0), down, 100.0, &QueryFilter::default()).unwrap();
+        assert_eq!(hit.entity, 2);
+        assert!((hit.distance - 4.5).abs() < 1e-4);
+        assert!((hit.normal.y - 1.0).abs() < 1e-4);
+
+        let hits = world.raycast_all(Vector3::new(0.0, 10.0, 0.0), down, 100.0, &QueryFilter::default());
+        assert_eq!(hits.iter().map(|hit| hit.entity).collect::<Vec<_>>(), vec![2, 1, 0]);
+        assert!(hits.windows(2).all(|pair| pair[0].distance < pair[1].distance));
+
+        let filter = QueryFilter { exclude_entity: Some(2), ..Default::default() };
+        assert_eq!(world.raycast(Vector3::new(0.0, 10.0, 0.0), down, 100.0, &filter).unwrap().entity, 1);
+        assert!(world.raycast(Vector3::new(0.0, 10.0, 0.0), down, 4.0, &QueryFilter::default()).is_none());
+
+        // a zero direction has nowhere to go
+        assert!(world.raycast(Vector3::new(0.0, 10.0, 0.0), Vector3::zeros(), 100.0, &QueryFilter::default()).is_none());
+        assert!(world.raycast_all(Vector3::new(0.0, 2.0, 0.0), Vector3::zeros(), 100.0, &QueryFilter::default()).is_empty());
+    }
+
+    #[test]
+    fn shape_casts_stop_at_the_first_obstacle() {
+        let world = query_world();
+        let sphere = ColliderShape::Sphere { radius: 0.5 };
+        let origin = Vector3::new(0.0, 10.0, 0.0);
+
+        let hit = world.shape_cast(&sphere, origin, Quaternion::identity(), -Vector3::unit_y(), 100.0, &QueryFilter::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(hit.entity, 2);
+        assert!((hit.distance - 4.0).abs() < 1e-3);
+        assert!((hit.point.y - 5.5).abs() < 1e-3);
+
+        let filter = QueryFilter { exclude_entity: Some(2), ..Default::default() };
+        let hit = world.shape_cast(&sphere, origin, Quaternion::identity(), -Vector3::unit_y(), 100.0, &filter).unwrap();
+        assert_eq!(hit.map(|hit| hit.entity), Some(1));
+
+        assert!(world.shape_cast(&sphere, origin, Quaternion::identity(), Vector3::zeros(), 100.0, &QueryFilter::default()).unwrap().is_none());
+        let invalid = ColliderShape::ConvexHull { points: Vec::new() };
+        assert!(world.shape_cast(&invalid, origin, Quaternion::identity(), -Vector3::unit_y(), 100.0, &QueryFilter::default()).is_err());
+    }
+
+    #[test]
+    fn overlaps_and_filters() {
+        let world = query_world();
+        let all = QueryFilter::default();
+        assert_eq!(world.overlap_sphere(Vector3::new(0.0, 2.0, 0.0), 0.1, &all), vec![1]);
+        assert_eq!(world.overlap_box(Vector3::new(0.0, 3.5, 0.0), Vector3::new(1.0, 2.0, 1.0), Quaternion::identity(), &all), vec![1, 2]);
+        // lying on its side, the capsule reaches from the sensor to box 4
+        let sideways = Quaternion::from_axis_angle(&Vector3::unit_z(), std::f32::consts::FRAC_PI_2);
+        assert_eq!(world.overlap_capsule(Vector3::new(7.5, 1.0, 0.0), 4.0, 0.2, sideways, &all), vec![3, 4]);
+        let shape = ColliderShape::Cylinder { height: 10.0, radius: 0.2 };
+        assert_eq!(world.overlap_shape(&shape, Vector3::new(0.0, 5.0, 0.0), Quaternion::identity(), &all).unwrap(), vec![0, 1, 2]);
+
+        let solid = QueryFilter { exclude_sensors: true, ..Default::default() };
+        assert_eq!(world.overlap_capsule(Vector3::new(7.5, 1.0, 0.0), 4.0, 0.2, sideways, &solid), vec![4]);
+        assert_eq!(world.overlap_capsule(Vector3::new(7.5, 1.0, 0.0), 4.0, 0.2, sideways, &QueryFilter::groups(1)), vec![3]);
+        // the sensor is a member of every group
+        assert_eq!(world.overlap_capsule(Vector3::new(7.5, 1.0, 0.0), 4.0, 0.2, sideways, &QueryFilter::groups(2)), vec![3, 4]);
+    }
+
+    #[test]
+    fn points() {
+        let world = query_world();
+        let all = QueryFilter::default();
+
+        let outside = world.project_point(Vector3::new(0.0, 3.0, 0.0), &all).unwrap();
+        assert_eq!(outside.entity, 1);
+        assert!(!outside.is_inside);
+        assert!((outside.point.y - 2.5).abs() < 1e-4);
+
+        let inside = world.project_point(Vector3::new(0.0, 2.1, 0.0), &all).unwrap();
+        assert_eq!(inside.entity, 1);
+        assert!(inside.is_inside);
+
+        assert!(world.contains_point(Vector3::new(5.0, 1.0, 0.0), &all));
+        assert!(!world.contains_point(Vector3::new(5.0, 1.0, 0.0), &QueryFilter { exclude_sensors: true, ..Default::default() }));
+        assert!(!world.contains_point(Vector3::new(10.0, 1.0, 0.0), &QueryFilter::groups(1)));
+        assert_eq!(world.entities_containing_point(Vector3::new(10.0, 1.0, 0.0), &QueryFilter::groups(2)), vec![4]);
+    }
+}
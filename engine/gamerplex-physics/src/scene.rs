@@ -0,0 +1,289 @@
+use gamerplex_math::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::integration::rapier;
+use crate::material::{self, PhysicsMaterial};
+use crate::{Body, ColliderDef, ColliderHandle, EntityId, JointDef, MassMode, MaterialId, PhysicsError, SimulationSettings, World};
+
+// A world written by hand or exported from one, in RON or JSON. Covers bodies, their
+// colliders, materials, joints and the solver settings. Triggers, force fields, vehicles
+// and character controllers are set up in code on top of the loaded world.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scene {
+    pub gravity: Vector3,
+    pub step_rate: f32,
+    pub settings: SimulationSettings,
+    pub materials: Vec<SceneMaterial>, // registered in order, a built-in name replaces that material and the first new one gets the id after MaterialId::WOOD
+    pub bodies: Vec<SceneBody>,
+    pub joints: Vec<SceneJoint>,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self {
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            step_rate: 60.0,
+            settings: SimulationSettings::default(),
+            materials: Vec::new(),
+            bodies: Vec::new(),
+            joints: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneMaterial {
+    pub name: String,
+    pub material: PhysicsMaterial,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneBody {
+    pub entity: EntityId, // loaded under this id, at most one body per entity
+    #[serde(default)]
+    pub body: Body,
+    #[serde(default)]
+    pub colliders: Vec<ColliderDef>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneJoint {
+    pub entity_a: EntityId,
+    pub entity_b: EntityId,
+    #[serde(default)]
+    pub joint: JointDef,
+}
+
+impl Scene {
+    pub fn from_ron(text: &str) -> Result<Self, PhysicsError> {
+        ron::from_str(text).map_err(|err| PhysicsError::InvalidScene(err.to_string()))
+    }
+
+    pub fn to_ron(&self) -> Result<String, PhysicsError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| PhysicsError::InvalidScene(err.to_string()))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, PhysicsError> {
+        serde_json::from_str(text).map_err(|err| PhysicsError::InvalidScene(err.to_string()))
+    }
+
+    pub fn to_json(&self) -> Result<String, PhysicsError> {
+        serde_json::to_string_pretty(self).map_err(|err| PhysicsError::InvalidScene(err.to_string()))
+    }
+}
+
+impl World {
+    // Queries work right away, the query pipeline is updated before returning
+    pub fn from_scene(scene: &Scene) -> Result<World, PhysicsError> {
+        if !scene.step_rate.is_finite() || scene.step_rate <= 0.0 {
+            return Err(PhysicsError::InvalidScene(format!("step rate {} is not a positive number", scene.step_rate)));
+        }
+
+        let mut world = World::new(scene.gravity);
+        world.set_step_rate(scene.step_rate);
+        world.set_simulation_settings(&scene.settings);
+
+        for material in &scene.materials {
+            world.register_material(&material.name, material.material.clone());
+        }
+
+        for body in &scene.bodies {
+            if world.entity_body_map.contains_key(&body.entity) {
+                return Err(PhysicsError::InvalidScene(format!("entity {} has more than one body", body.entity)));
+            }
+            let handle = world.add_rigid_body(body.entity, &body.body);
+            for collider in &body.colliders {
+                world.add_collider(body.entity, handle, collider)?;
+            }
+        }
+
+        for joint in &scene.joints {
+            world.add_joint(joint.entity_a, joint.entity_b, &joint.joint)?;
+        }
+
+        world.update_query_pipeline();
+        Ok(world)
+    }
+
+    // Bodies come out sorted by entity, each with the colliders attached to it
+    pub fn to_scene(&self) -> Scene {
+        let mut entities: Vec<_> = self.entity_body_map.iter().collect();
+        entities.sort_by_key(|(entity, _)| **entity);
+
+        let bodies = entities
+            .into_iter()
+            .map(|(entity, handle)| {
+                let handle = handle.to_rapier_handle();
+                let body = &self.rigid_body_set[handle];
+                let mass = self.mass_modes.get(&handle).cloned().unwrap_or(MassMode::Computed);
+
+                let colliders = body.colliders()
+                    .iter()
+                    .filter_map(|collider| {
+                        let shape = self.collider_shapes.get(collider)?.clone();
+                        let material = self.collider_material(ColliderHandle::from_rapier_handle(*collider))
+                            .unwrap_or(MaterialId::DEFAULT);
+                        let mut def = rapier::collider_def(&self.collider_set[*collider], shape, material);
+                        if let Some(density) = self.custom_mass_densities.get(collider) {
+                            def.density = *density;
+                        }
+                        Some(def)
+                    })
+                    .collect();
+
+                SceneBody { entity: *entity, body: rapier::body_def(body, mass), colliders }
+            })
+            .collect();
+
+        let joints = self.impulse_joint_set
+            .iter()
+            .filter_map(|(handle, joint)| {
+                let info = self.joints.get(&handle)?;
+                Some(SceneJoint {
+                    entity_a: info.entity_a,
+                    entity_b: info.entity_b,
+                    joint: rapier::joint_def(&joint.data, info),
+                })
+            })
+            .collect();
+
+        // built-in materials only when they were replaced
+        let defaults = material::default_materials();
+        let materials = self.materials
+            .iter()
+            .enumerate()
+            .filter(|(index, entry)| defaults.get(*index) != Some(*entry))
+            .map(|(_, (name, material))| SceneMaterial { name: name.clone(), material: material.clone() })
+            .collect();
+
+        Scene {
+            gravity: self.gravity,
+            step_rate: self.step_rate(),
+            settings: self.simulation_settings(),
+            materials,
+            bodies,
+            joints,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BodyType, ColliderShape, CompoundPart, JointLimit, JointMotor, JointAxis, JointType, MotorTarget};
+    use gamerplex_math::Quaternion;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn hand_written_ron() {
+        let scene = Scene::from_ron(r#"(
+            gravity: (x: 0.0, y: -20.0, z: 0.0),
+            materials: [(name: "bouncy", material: (friction: 0.2, restitution: 0.9, friction_combine: Average, restitution_combine: Max, surface: 7))],
+            bodies: [
+                (entity: 100, body: (body_type: Static), colliders: [(shape: Box(half_extents: (x: 10.0, y: 0.5, z: 10.0)))]),
+                (entity: 7, body: (position: (x: 0.0, y: 3.0, z: 0.0)), colliders: [(shape: Sphere(radius: 0.5), material: (5))]),
+            ],
+        )"#).unwrap();
+
+        let mut world = World::from_scene(&scene).unwrap();
+        assert_eq!(world.body_entity(world.body_handle(7).unwrap()), Some(7));
+        assert_eq!(world.collider_material(world.colliders(7)[0]).unwrap(), world.material_id("bouncy").unwrap());
+        assert!(world.raycast(Vector3::new(0.0, 10.0, 0.0), -Vector3::unit_y(), 20.0, &Default::default()).is_some_and(|hit| hit.entity == 7));
+
+        for _ in 0..30 {
+            world.step(DT);
+        }
+        assert!(world.rigid_body(7).unwrap().translation().y < 3.0);
+    }
+
+    fn sample_world() -> World {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+        world.set_step_rate(120.0);
+        let slime = world.register_material("slime", PhysicsMaterial { friction: 0.9, surface: 9, ..Default::default() });
+        world.register_material("ice", PhysicsMaterial { friction: 0.1, ..PhysicsMaterial::ice() });
+
+        let ground = world.add_rigid_body(3, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(3, ground, &ColliderDef {
+            shape: ColliderShape::HeightField { heights: vec![0.0, 0.5, 0.5, 0.0], rows: 2, columns: 2, scale: Vector3::new(20.0, 1.0, 20.0) },
+            material: MaterialId::ICE,
+            ..Default::default()
+        }).unwrap();
+
+        let door = world.add_rigid_body(1, &Body {
+            position: Vector3::new(1.0, 2.0, 3.0),
+            rotation: Quaternion::from_axis_angle(&Vector3::unit_y(), 0.5),
+            mass: MassMode::Total(40.0),
+            can_sleep: false,
+            soft_ccd_prediction: 0.5,
+            ..Default::default()
+        });
+        world.add_collider(1, door, &ColliderDef {
+            shape: ColliderShape::Compound { parts: vec![CompoundPart {
+                shape: ColliderShape::RoundBox { half_extents: Vector3::new(0.5, 1.0, 0.05), border_radius: 0.02 },
+                position: Vector3::unit_x(),
+                rotation: Quaternion::identity(),
+            }] },
+            material: slime,
+            contact_force_threshold: Some(100.0),
+            ..Default::default()
+        }).unwrap();
+        world.add_collider(1, door, &ColliderDef {
+            shape: ColliderShape::Capsule { height: 1.0, radius: 0.1 },
+            position: Vector3::new(0.0, 0.5, 0.0),
+            is_sensor: true,
+            ..Default::default()
+        }).unwrap();
+
+        world.add_joint(3, 1, &JointDef {
+            joint_type: JointType::Revolute { axis: Vector3::unit_y() },
+            local_anchor_a: Vector3::new(1.0, 2.0, 3.0),
+            limits: vec![JointLimit { axis: JointAxis::AngX, min: -1.0, max: 1.0 }],
+            motors: vec![JointMotor { axis: JointAxis::AngX, target: MotorTarget::Velocity { velocity: 2.0, factor: 0.5 }, max_force: 50.0 }],
+            break_force: Some(1000.0),
+            ..Default::default()
+        }).unwrap();
+        world
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let world = sample_world();
+        let ron = world.to_scene().to_ron().unwrap();
+        let json = world.to_scene().to_json().unwrap();
+
+        let from_ron = World::from_scene(&Scene::from_ron(&ron).unwrap()).unwrap();
+        let from_json = World::from_scene(&Scene::from_json(&json).unwrap()).unwrap();
+        assert_eq!(from_ron.to_scene().to_ron().unwrap(), ron);
+        assert_eq!(from_json.to_scene().to_ron().unwrap(), ron);
+
+        assert!((from_ron.step_rate() - 120.0).abs() < 1e-3);
+        assert_eq!(from_ron.mass_properties(1).unwrap().mass, 40.0);
+        assert_eq!(from_ron.material(MaterialId::ICE).unwrap().friction, 0.1);
+        assert_eq!(from_ron.material_id("slime"), world.material_id("slime"));
+        assert_eq!(world.to_scene().materials.len(), 2);
+        assert_eq!(from_ron.colliders(1).len(), 2);
+        let joints = from_ron.to_scene().joints;
+        assert_eq!((joints[0].entity_a, joints[0].entity_b), (3, 1));
+    }
+
+    #[test]
+    fn invalid_scenes() {
+        assert!(matches!(Scene::from_json("{ \"bodies\": 3 }"), Err(PhysicsError::InvalidScene(_))));
+
+        let body = SceneBody { entity: 1, body: Body::default(), colliders: Vec::new() };
+        let scene = Scene { bodies: vec![body.clone(), body], ..Default::default() };
+        assert!(matches!(World::from_scene(&scene), Err(PhysicsError::InvalidScene(_))));
+
+        let scene = Scene::from_ron("(step_rate: 0.0)").unwrap();
+        assert!(matches!(World::from_scene(&scene), Err(PhysicsError::InvalidScene(_))));
+
+        let scene = Scene {
+            joints: vec![SceneJoint { entity_a: 1, entity_b: 2, joint: JointDef::default() }],
+            ..Default::default()
+        };
+        assert_eq!(World::from_scene(&scene).err(), Some(PhysicsError::NoRigidBody(1)));
+    }
+}
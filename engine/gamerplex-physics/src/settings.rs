@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+use crate::integration::rapier;
+use crate::World;
+
+// Solver tuning, e.g. more iterations for a level built from tall stacks. The
+// defaults are Rapier's, lengths are in meters scaled by length_unit.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SimulationSettings {
+    pub solver_iterations: usize,           // at least 1, more makes stacks and joint chains stiffer
+    pub friction_iterations: usize,         // extra friction passes after the last solver iteration
+    pub stabilization_iterations: usize,    // penetration correction passes per solver iteration
+    pub contact_natural_frequency: f32,     // higher pushes overlapping bodies apart faster, but may jitter
+    pub contact_damping_ratio: f32,         // higher makes contacts softer, allowing more visible penetration
+    pub joint_natural_frequency: f32,
+    pub joint_damping_ratio: f32,
+    pub allowed_penetration: f32,           // overlap the solver leaves alone, avoids jitter on resting contacts
+    pub max_penetration_correction: f32,    // fastest the solver may push overlapping bodies apart, in m/s
+    pub prediction_distance: f32,           // bodies this close already get contacts
+    pub length_unit: f32,                   // size of a typical dynamic body, scales the tolerances above
+    pub max_ccd_substeps: usize,            // at least 1, times a step may be split at CCD impacts
+    pub min_ccd_dt: f32,                    // shortest CCD substep
+}
+
+impl Default for SimulationSettings {
+    fn default() -> Self {
+        rapier::simulation_settings(&Default::default())
+    }
+}
+
+impl World {
+    pub fn simulation_settings(&self) -> SimulationSettings {
+        rapier::simulation_settings(&self.integration_parameters)
+    }
+
+    // Takes effect on the next step, the timestep itself is set through set_step_rate
+    pub fn set_simulation_settings(&mut self, settings: &SimulationSettings) {
+        rapier::apply_simulation_settings(&mut self.integration_parameters, settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, ColliderShape};
+    use gamerplex_math::Vector3;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn settings_round_trip() {
+        let mut world = World::new(Vector3::zeros());
+        assert_eq!(world.simulation_settings(), SimulationSettings::default());
+
+        let settings = SimulationSettings {
+            solver_iterations: 8,
+            allowed_penetration: 0.01,
+            max_ccd_substeps: 4,
+            ..Default::default()
+        };
+        world.set_simulation_settings(&settings);
+        world.set_step_rate(120.0);
+        assert_eq!(world.simulation_settings(), settings);
+
+        world.set_simulation_settings(&SimulationSettings { solver_iterations: 0, max_ccd_substeps: 0, ..settings });
+        assert_eq!(world.simulation_settings().solver_iterations, 1);
+        assert_eq!(world.simulation_settings().max_ccd_substeps, 1);
+    }
+
+    // Shoots a small ball at a thin wall, returns whether it got through
+    fn tunnels(speed: f32, ccd_enabled: bool, soft_ccd_prediction: f32) -> (bool, Vec<u32>) {
+        let mut world = World::new(Vector3::zeros());
+        let wall = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, wall, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(0.05, 5.0, 5.0) },
+            ..Default::default()
+        }).unwrap();
+        let bullet = world.add_rigid_body(1, &Body {
+            position: Vector3::new(-5.5, 0.0, 0.0),
+            linear_velocity: Vector3::new(speed, 0.0, 0.0),
+            ccd_enabled,
+            soft_ccd_prediction,
+            ..Default::default()
+        });
+        world.add_collider(1, bullet, &ColliderDef {
+            shape: ColliderShape::Sphere { radius: 0.1 },
+            ..Default::default()
+        }).unwrap();
+
+        let mut ccd_bodies = Vec::new();
+        for _ in 0..10 {
+            world.step(DT);
+            ccd_bodies.extend_from_slice(world.ccd_active_bodies());
+        }
+        (world.rigid_body(1).unwrap().translation().x > 0.0, ccd_bodies)
+    }
+
+    #[test]
+    fn ccd_stops_fast_bodies() {
+        assert_eq!(tunnels(400.0, false, 0.0), (true, vec![]));
+
+        let (tunneled, ccd_bodies) = tunnels(400.0, true, 0.0);
+        assert!(!tunneled);
+        assert!(ccd_bodies.contains(&1));
+
+        // soft-CCD is meant for moderately fast bodies
+        assert!(tunnels(60.0, false, 0.0).0);
+        assert!(!tunnels(60.0, false, 2.0).0);
+    }
+}
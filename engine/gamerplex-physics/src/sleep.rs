@@ -0,0 +1,251 @@
+use std::collections::{HashSet, VecDeque};
+
+use rapier3d::prelude::{RigidBodyActivation, RigidBodyHandle};
+
+use crate::{EntityId, PhysicsError, World};
+
+// A body falls asleep once it stayed below both velocities for time_until_sleep seconds.
+// Negative velocities keep it awake, like Body::can_sleep = false.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SleepThresholds {
+    pub linear_velocity: f32,  // m/s, scaled by SimulationSettings::length_unit
+    pub angular_velocity: f32, // rad/s
+    pub time_until_sleep: f32,
+}
+
+impl Default for SleepThresholds {
+    fn default() -> Self {
+        Self {
+            linear_velocity: RigidBodyActivation::default_normalized_linear_threshold(),
+            angular_velocity: RigidBodyActivation::default_angular_threshold(),
+            time_until_sleep: RigidBodyActivation::default_time_until_sleep(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SleepEventType {
+    FellAsleep,
+    WokeUp,
+}
+
+// Bodies of one island that changed state during the same substep, Rapier puts
+// whole islands to sleep and wakes them together
+#[derive(Clone, Debug)]
+pub struct SleepEvent {
+    pub entities: Vec<EntityId>, // sorted
+    pub event_type: SleepEventType,
+}
+
+impl World {
+    pub fn is_sleeping(&self, entity: EntityId) -> Result<bool, PhysicsError> {
+        Ok(self.rigid_body(entity)?.is_sleeping())
+    }
+
+    // Stops the body until something touches it or it is woken up, its velocity is lost.
+    // Bodies with negative thresholds wake up again on the next step.
+    pub fn sleep(&mut self, entity: EntityId) -> Result<(), PhysicsError> {
+        self.dynamic_body_mut(entity)?.sleep();
+        Ok(())
+    }
+
+    pub fn sleep_thresholds(&self, entity: EntityId) -> Result<SleepThresholds, PhysicsError> {
+        let activation = self.rigid_body(entity)?.activation();
+        Ok(SleepThresholds {
+            linear_velocity: activation.normalized_linear_threshold,
+            angular_velocity: activation.angular_threshold,
+            time_until_sleep: activation.time_until_sleep,
+        })
+    }
+
+    pub fn set_sleep_thresholds(&mut self, entity: EntityId, thresholds: &SleepThresholds) -> Result<(), PhysicsError> {
+        let activation = self.rigid_body_mut(entity)?.activation_mut();
+        activation.normalized_linear_threshold = thresholds.linear_velocity;
+        activation.angular_threshold = thresholds.angular_velocity;
+        activation.time_until_sleep = thresholds.time_until_sleep;
+        Ok(())
+    }
+
+    // Dynamic entities connected to this one through contacts and joints, including
+    // itself and sorted. Static and kinematic bodies do not connect islands.
+    pub fn island_entities(&self, entity: EntityId) -> Result<Vec<EntityId>, PhysicsError> {
+        let handle = self.entity_body_map.get(&entity).ok_or(PhysicsError::NoRigidBody(entity))?.to_rapier_handle();
+        if !self.rigid_body_set[handle].is_dynamic() {
+            return Err(PhysicsError::BodyNotDynamic(entity));
+        }
+
+        let mut entities: Vec<_> = self.island_of(handle, |_| true)
+            .into_iter()
+            .filter_map(|body| self.body_entity_map.get(&body).copied())
+            .collect();
+        entities.sort_unstable();
+        Ok(entities)
+    }
+
+    // Events accumulate until `clear_events` is called
+    pub fn sleep_events(&self) -> &[SleepEvent] {
+        &self.sleep_events
+    }
+
+    // Called after every substep, compares every body against the state seen last time
+    pub(crate) fn track_sleep_changes(&mut self) {
+        let mut fell_asleep = HashSet::new();
+        let mut woke_up = HashSet::new();
+
+        for (handle, body) in self.rigid_body_set.iter().filter(|(_, body)| !body.is_fixed()) {
+            match (body.is_sleeping(), self.sleeping_bodies.contains(&handle)) {
+                (true, false) => fell_asleep.insert(handle),
+                (false, true) => woke_up.insert(handle),
+                _ => continue,
+            };
+        }
+
+        for handle in &fell_asleep {
+            self.sleeping_bodies.insert(*handle);
+        }
+        for handle in &woke_up {
+            self.sleeping_bodies.remove(handle);
+        }
+
+        let mut events = self.group_into_islands(fell_asleep, SleepEventType::FellAsleep);
+        events.extend(self.group_into_islands(woke_up, SleepEventType::WokeUp));
+        self.sleep_events.extend(events);
+    }
+
+    fn group_into_islands(&self, mut changed: HashSet<RigidBodyHandle>, event_type: SleepEventType) -> Vec<SleepEvent> {
+        let mut events = Vec::new();
+
+        while let Some(start) = changed.iter().next().copied() {
+            let island = self.island_of(start, |body| changed.contains(&body));
+            for body in &island {
+                changed.remove(body);
+            }
+
+            let mut entities: Vec<_> = island
+                .iter()
+                .filter_map(|body| self.body_entity_map.get(body).copied())
+                .collect();
+            entities.sort_unstable();
+            if !entities.is_empty() {
+                events.push(SleepEvent { entities, event_type: event_type.clone() });
+            }
+        }
+
+        // the hash set iterates in no particular order
+        events.sort_by_key(|event| event.entities[0]);
+        events
+    }
+
+    // Breadth-first walk over touching contacts and joints, only entering dynamic bodies accepted by `include`
+    fn island_of(&self, start: RigidBodyHandle, include: impl Fn(RigidBodyHandle) -> bool) -> Vec<RigidBodyHandle> {
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        let mut island = Vec::new();
+
+        while let Some(handle) = queue.pop_front() {
+            island.push(handle);
+            let body = &self.rigid_body_set[handle];
+
+            let touching = body.colliders().iter().flat_map(|collider| {
+                self.narrow_phase
+                    .contact_pairs_with(*collider)
+                    .filter(|pair| pair.has_any_active_contact)
+                    .map(move |pair| if pair.collider1 == *collider { pair.collider2 } else { pair.collider1 })
+                    .filter_map(|other| self.collider_set.get(other)?.parent())
+            });
+            let jointed = self.impulse_joint_set
+                .attached_joints(handle)
+                .map(|(body1, body2, _, _)| if body1 == handle { body2 } else { body1 });
+
+            for neighbor in touching.chain(jointed) {
+                let dynamic = self.rigid_body_set.get(neighbor).is_some_and(|body| body.is_dynamic());
+                if dynamic && include(neighbor) && visited.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        island
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, ColliderShape};
+    use gamerplex_math::Vector3;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    // Two boxes stacked on the ground and one on its own
+    fn stacks_world() -> World {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+        let ground = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, ground, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(20.0, 0.5, 20.0) },
+            ..Default::default()
+        }).unwrap();
+
+        for (entity, position) in [(1, Vector3::new(0.0, 1.0, 0.0)), (2, Vector3::new(0.0, 2.0, 0.0)), (3, Vector3::new(5.0, 1.0, 0.0))] {
+            let body = world.add_rigid_body(entity, &Body { position, ..Default::default() });
+            world.add_collider(entity, body, &ColliderDef::default()).unwrap();
+        }
+        world
+    }
+
+    fn events_of(world: &World, event_type: SleepEventType) -> Vec<Vec<EntityId>> {
+        world.sleep_events()
+            .iter()
+            .filter(|event| event.event_type == event_type)
+            .map(|event| event.entities.clone())
+            .collect()
+    }
+
+    #[test]
+    fn islands_sleep_and_wake_together() {
+        let mut world = stacks_world();
+        world.step(DT);
+        assert_eq!(world.island_entities(2).unwrap(), vec![1, 2]);
+        assert_eq!(world.island_entities(3).unwrap(), vec![3]);
+        assert_eq!(world.island_entities(0), Err(PhysicsError::BodyNotDynamic(0)));
+
+        for _ in 0..300 {
+            world.step(DT);
+        }
+        assert!(world.is_sleeping(1).unwrap() && world.is_sleeping(2).unwrap());
+        assert_eq!(events_of(&world, SleepEventType::FellAsleep), vec![vec![1, 2], vec![3]]);
+        // sleeping islands keep their contacts
+        assert_eq!(world.island_entities(1).unwrap(), vec![1, 2]);
+
+        world.clear_events();
+        world.wake_up(2).unwrap();
+        world.step(DT);
+        assert!(!world.is_sleeping(1).unwrap());
+        assert!(world.is_sleeping(3).unwrap());
+        assert_eq!(events_of(&world, SleepEventType::WokeUp), vec![vec![1, 2]]);
+    }
+
+    #[test]
+    fn forced_sleep_and_thresholds() {
+        let mut world = stacks_world();
+        world.set_sleep_thresholds(3, &SleepThresholds { linear_velocity: -1.0, ..Default::default() }).unwrap();
+        assert_eq!(world.sleep_thresholds(3).unwrap().linear_velocity, -1.0);
+
+        for _ in 0..300 {
+            world.step(DT);
+        }
+        assert!(world.is_sleeping(1).unwrap());
+        assert!(!world.is_sleeping(3).unwrap());
+
+        // a falling body stops in mid air
+        let mut world = stacks_world();
+        world.step(DT);
+        world.clear_events();
+        world.sleep(3).unwrap();
+        world.step(DT);
+        assert!(world.is_sleeping(3).unwrap());
+        assert_eq!(world.linear_velocity(3).unwrap(), Vector3::zeros());
+        assert_eq!(events_of(&world, SleepEventType::FellAsleep), vec![vec![3]]);
+        assert_eq!(world.sleep(0), Err(PhysicsError::BodyNotDynamic(0)));
+    }
+}
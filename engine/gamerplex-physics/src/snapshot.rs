@@ -0,0 +1,323 @@
+use std::collections::{HashMap, HashSet};
+
+use gamerplex_math::Vector3;
+use rapier3d::prelude::{
+    CCDSolver,
+    ColliderHandle as RapierColliderHandle,
+    ColliderSet,
+    DefaultBroadPhase,
+    ImpulseJointHandle,
+    ImpulseJointSet,
+    IntegrationParameters,
+    IslandManager,
+    Isometry,
+    MultibodyJointSet,
+    NarrowPhase,
+    QueryPipeline,
+    RigidBodyHandle,
+    RigidBodySet,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::character::CharacterController;
+use crate::events::{ActiveContact, ContactKey};
+use crate::joint::JointInfo;
+use crate::trigger::{Trigger, TriggerPairs};
+use crate::vehicle::Vehicle;
+use crate::forces::ForceField;
+use crate::{BodyHandle, ColliderHandle, ColliderShape, EntityId, MassMode, PhysicsError, PhysicsMaterial, World};
+
+// Everything the next step depends on. Field order must match WorldState,
+// bincode encodes structs positionally.
+#[derive(Serialize)]
+struct WorldStateRef<'a> {
+    rigid_body_set: &'a RigidBodySet,
+    collider_set: &'a ColliderSet,
+    integration_parameters: &'a IntegrationParameters,
+    island_manager: &'a IslandManager,
+    broad_phase: &'a DefaultBroadPhase,
+    narrow_phase: &'a NarrowPhase,
+    impulse_joint_set: &'a ImpulseJointSet,
+    multibody_joint_set: &'a MultibodyJointSet,
+    ccd_solver: &'a CCDSolver,
+    query_pipeline: &'a QueryPipeline,
+    gravity: Vector3,
+    simulation_rate: f32,
+    entity_body_map: &'a HashMap<EntityId, BodyHandle>,
+    body_entity_map: &'a HashMap<RigidBodyHandle, EntityId>,
+    entity_collider_map: &'a HashMap<EntityId, Vec<ColliderHandle>>,
+    collider_entity_map: &'a HashMap<RapierColliderHandle, EntityId>,
+    collider_shapes: &'a HashMap<RapierColliderHandle, ColliderShape>,
+    active_contacts: &'a HashMap<ContactKey, ActiveContact>,
+    joints: &'a HashMap<ImpulseJointHandle, JointInfo>,
+    character_controllers: &'a HashMap<EntityId, CharacterController>,
+    vehicles: &'a HashMap<RigidBodyHandle, Vehicle>,
+    force_fields: &'a HashMap<EntityId, ForceField>,
+    triggers: &'a HashMap<EntityId, Trigger>,
+    trigger_pairs: &'a TriggerPairs,
+    sleeping_bodies: &'a HashSet<RigidBodyHandle>,
+    mass_modes: &'a HashMap<RigidBodyHandle, MassMode>,
+    custom_mass_densities: &'a HashMap<RapierColliderHandle, f32>,
+    materials: &'a Vec<(String, PhysicsMaterial)>,
+    accumulated_time: f32,
+    max_substeps: u32,
+    previous_poses: &'a HashMap<RigidBodyHandle, Isometry<f32>>,
+}
+
+#[derive(Deserialize)]
+struct WorldState {
+    rigid_body_set: RigidBodySet,
+    collider_set: ColliderSet,
+    integration_parameters: IntegrationParameters,
+    island_manager: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    impulse_joint_set: ImpulseJointSet,
+    multibody_joint_set: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+    query_pipeline: QueryPipeline,
+    gravity: Vector3,
+    simulation_rate: f32,
+    entity_body_map: HashMap<EntityId, BodyHandle>,
+    body_entity_map: HashMap<RigidBodyHandle, EntityId>,
+    entity_collider_map: HashMap<EntityId, Vec<ColliderHandle>>,
+    collider_entity_map: HashMap<RapierColliderHandle, EntityId>,
+    collider_shapes: HashMap<RapierColliderHandle, ColliderShape>,
+    active_contacts: HashMap<ContactKey, ActiveContact>,
+    joints: HashMap<ImpulseJointHandle, JointInfo>,
+    character_controllers: HashMap<EntityId, CharacterController>,
+    vehicles: HashMap<RigidBodyHandle, Vehicle>,
+    force_fields: HashMap<EntityId, ForceField>,
+    triggers: HashMap<EntityId, Trigger>,
+    trigger_pairs: TriggerPairs,
+    sleeping_bodies: HashSet<RigidBodyHandle>,
+    mass_modes: HashMap<RigidBodyHandle, MassMode>,
+    custom_mass_densities: HashMap<RapierColliderHandle, f32>,
+    materials: Vec<(String, PhysicsMaterial)>,
+    accumulated_time: f32,
+    max_substeps: u32,
+    previous_poses: HashMap<RigidBodyHandle, Isometry<f32>>,
+}
+
+// Simulating the same inputs from the same snapshot gives bit-identical results on
+// the same build. Enable the `enhanced-determinism` feature to extend that across
+// platforms, e.g. for lockstep networking between different machines.
+impl World {
+    // Serializes the whole simulation state, events that have not been read yet are not included
+    pub fn snapshot(&self) -> Result<Vec<u8>, PhysicsError> {
+        let state = WorldStateRef {
+            rigid_body_set: &self.rigid_body_set,
+            collider_set: &self.collider_set,
+            integration_parameters: &self.integration_parameters,
+            island_manager: &self.island_manager,
+            broad_phase: &self.broad_phase,
+            narrow_phase: &self.narrow_phase,
+            impulse_joint_set: &self.impulse_joint_set,
+            multibody_joint_set: &self.multibody_joint_set,
+            ccd_solver: &self.ccd_solver,
+            query_pipeline: &self.query_pipeline,
+            gravity: self.gravity,
+            simulation_rate: self.simulation_rate,
+            entity_body_map: &self.entity_body_map,
+            body_entity_map: &self.body_entity_map,
+            entity_collider_map: &self.entity_collider_map,
+            collider_entity_map: &self.collider_entity_map,
+            collider_shapes: &self.collider_shapes,
+            active_contacts: &self.active_contacts,
+            joints: &self.joints,
+            character_controllers: &self.character_controllers,
+            vehicles: &self.vehicles,
+            force_fields: &self.force_fields,
+            triggers: &self.triggers,
+            trigger_pairs: &self.trigger_pairs,
+            sleeping_bodies: &self.sleeping_bodies,
+            mass_modes: &self.mass_modes,
+            custom_mass_densities: &self.custom_mass_densities,
+            materials: &self.materials,
+            accumulated_time: self.accumulated_time,
+            max_substeps: self.max_substeps,
+            previous_poses: &self.previous_poses,
+        };
+
+        bincode::serialize(&state).map_err(|err| PhysicsError::InvalidSnapshot(err.to_string()))
+    }
+
+    // Replaces the whole simulation state, pending events are dropped. On error the world is left untouched.
+    pub fn restore(&mut self, snapshot: &[u8]) -> Result<(), PhysicsError> {
+        let state: WorldState = bincode::deserialize(snapshot)
+            .map_err(|err| PhysicsError::InvalidSnapshot(err.to_string()))?;
+
+        self.rigid_body_set = state.rigid_body_set;
+        self.collider_set = state.collider_set;
+        self.integration_parameters = state.integration_parameters;
+        self.island_manager = state.island_manager;
+        self.broad_phase = state.broad_phase;
+        self.narrow_phase = state.narrow_phase;
+        self.impulse_joint_set = state.impulse_joint_set;
+        self.multibody_joint_set = state.multibody_joint_set;
+        self.ccd_solver = state.ccd_solver;
+        self.query_pipeline = state.query_pipeline;
+        self.gravity = state.gravity;
+        self.simulation_rate = state.simulation_rate;
+        self.entity_body_map = state.entity_body_map;
+        self.body_entity_map = state.body_entity_map;
+        self.entity_collider_map = state.entity_collider_map;
+        self.collider_entity_map = state.collider_entity_map;
+        self.collider_shapes = state.collider_shapes;
+        self.active_contacts = state.active_contacts;
+        self.joints = state.joints;
+        self.character_controllers = state.character_controllers;
+        self.vehicles = state.vehicles;
+        self.force_fields = state.force_fields;
+        self.triggers = state.triggers;
+        self.trigger_pairs = state.trigger_pairs;
+        self.sleeping_bodies = state.sleeping_bodies;
+        self.mass_modes = state.mass_modes;
+        self.custom_mass_densities = state.custom_mass_densities;
+        self.materials = state.materials;
+        self.accumulated_time = state.accumulated_time;
+        self.max_substeps = state.max_substeps;
+        self.previous_poses = state.previous_poses;
+
+        // events refer to the timeline we just left
+        self.collision_events.clear();
+        self.contact_force_events.clear();
+        self.trigger_events.clear();
+        self.joint_broken_events.clear();
+        self.sleep_events.clear();
+        self.collision_recv.try_iter().for_each(drop);
+        self.contact_force_recv.try_iter().for_each(drop);
+
+        Ok(())
+    }
+
+    // Cheap hash of every body's pose and velocity, compare between peers to detect desyncs
+    pub fn state_checksum(&self) -> u64 {
+        // FNV-1a, stable across runs and platforms unlike std's hasher
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut write = |bits: u32| {
+            for byte in bits.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+
+        // the arena iterates in slot order, so this does not depend on hash map order
+        for (handle, body) in self.rigid_body_set.iter() {
+            let (index, generation) = handle.into_raw_parts();
+            write(index);
+            write(generation);
+
+            let position = body.position();
+            let values = position.translation.vector.iter()
+                .chain(position.rotation.coords.iter())
+                .chain(body.linvel().iter())
+                .chain(body.angvel().iter());
+            for value in values {
+                write(value.to_bits());
+            }
+        }
+
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, ColliderDef, ColliderShape, JointDef, JointType};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn build_scene() -> World {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+
+        let ground = world.add_rigid_body(0, &Body { body_type: crate::BodyType::Static, ..Default::default() });
+        world.add_collider(0, ground, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(10.0, 0.5, 10.0) },
+            ..Default::default()
+        }).unwrap();
+
+        // a wobbly stack so contacts, friction and islands are all involved
+        for i in 1..=5 {
+            let body = world.add_rigid_body(i, &Body {
+                position: Vector3::new(0.05 * i as f32, 0.5 + i as f32, 0.0),
+                ..Default::default()
+            });
+            world.add_collider(i, body, &ColliderDef {
+                shape: ColliderShape::Box { half_extents: Vector3::new(0.5, 0.5, 0.5) },
+                ..Default::default()
+            }).unwrap();
+        }
+
+        let pendulum = world.add_rigid_body(10, &Body { position: Vector3::new(3.0, 3.0, 0.0), ..Default::default() });
+        world.add_collider(10, pendulum, &ColliderDef::default()).unwrap();
+        world.add_joint(0, 10, &JointDef {
+            joint_type: JointType::Spherical,
+            local_anchor_a: Vector3::new(4.0, 5.0, 0.0),
+            ..Default::default()
+        }).unwrap();
+
+        world
+    }
+
+    fn simulate(world: &mut World, frames: usize) {
+        for _ in 0..frames {
+            world.step(DT);
+        }
+    }
+
+    #[test]
+    fn identical_inputs_give_identical_checksums() {
+        let mut a = build_scene();
+        let mut b = build_scene();
+
+        simulate(&mut a, 120);
+        simulate(&mut b, 120);
+
+        assert_eq!(a.state_checksum(), b.state_checksum());
+    }
+
+    #[test]
+    fn restore_resimulates_the_same_frames() {
+        let mut world = build_scene();
+        simulate(&mut world, 30);
+
+        let snapshot = world.snapshot().unwrap();
+        let checksum_at_snapshot = world.state_checksum();
+
+        simulate(&mut world, 90);
+        let expected = world.state_checksum();
+        assert_ne!(expected, checksum_at_snapshot);
+
+        world.restore(&snapshot).unwrap();
+        assert_eq!(world.state_checksum(), checksum_at_snapshot);
+
+        simulate(&mut world, 90);
+        assert_eq!(world.state_checksum(), expected);
+    }
+
+    #[test]
+    fn restore_into_a_fresh_world() {
+        let mut original = build_scene();
+        simulate(&mut original, 45);
+        let snapshot = original.snapshot().unwrap();
+
+        let mut copy = World::new(Vector3::zeros());
+        copy.restore(&snapshot).unwrap();
+        assert_eq!(copy.body_handle(3), original.body_handle(3));
+
+        simulate(&mut original, 60);
+        simulate(&mut copy, 60);
+        assert_eq!(copy.state_checksum(), original.state_checksum());
+    }
+
+    #[test]
+    fn restore_rejects_garbage() {
+        let mut world = build_scene();
+        let checksum = world.state_checksum();
+
+        assert!(matches!(world.restore(&[1, 2, 3]), Err(PhysicsError::InvalidSnapshot(_))));
+        assert_eq!(world.state_checksum(), checksum);
+    }
+}
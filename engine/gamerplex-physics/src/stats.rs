@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use rapier3d::prelude::PhysicsPipeline;
+
+use crate::World;
+
+// What the last call to `step` did, times are summed over its substeps
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StepStats {
+    pub substeps: u32,
+    pub step_time: Duration,         // the whole call, including events, force fields and vehicles
+    pub broad_phase_time: Duration,
+    pub narrow_phase_time: Duration,
+    pub island_time: Duration,       // building the islands of bodies in contact, and putting them to sleep
+    pub solver_time: Duration,
+    pub ccd_time: Duration,
+    pub active_bodies: usize,        // awake dynamic and kinematic bodies
+    pub sleeping_bodies: usize,
+    pub contact_pairs: usize,        // collider pairs actually touching
+}
+
+impl StepStats {
+    pub fn to_ms(time: Duration) -> f32 {
+        time.as_secs_f32() * 1000.0
+    }
+
+    // Adds the timings Rapier measured during one substep
+    pub(crate) fn add_substep(&mut self, pipeline: &PhysicsPipeline) {
+        let counters = &pipeline.counters;
+        self.substeps += 1;
+        self.broad_phase_time += counters.cd.broad_phase_time.time();
+        self.narrow_phase_time += counters.cd.narrow_phase_time.time();
+        self.island_time += counters.stages.island_construction_time.time();
+        self.solver_time += counters.stages.solver_time.time();
+        self.ccd_time += counters.ccd.toi_computation_time.time();
+    }
+}
+
+impl World {
+    pub fn step_stats(&self) -> &StepStats {
+        &self.step_stats
+    }
+
+    // Counts taken at the end of `step`
+    pub(crate) fn count_bodies_and_contacts(&mut self) {
+        let mut active_bodies = 0;
+        let mut sleeping_bodies = 0;
+        for (_, body) in self.rigid_body_set.iter().filter(|(_, body)| !body.is_fixed()) {
+            if body.is_sleeping() {
+                sleeping_bodies += 1;
+            } else {
+                active_bodies += 1;
+            }
+        }
+
+        self.step_stats.active_bodies = active_bodies;
+        self.step_stats.sleeping_bodies = sleeping_bodies;
+        self.step_stats.contact_pairs = self.narrow_phase
+            .contact_pairs()
+            .filter(|pair| pair.has_any_active_contact)
+            .count();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, ColliderShape};
+    use gamerplex_math::Vector3;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn stats_of_the_last_step() {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+        let ground = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, ground, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(10.0, 0.5, 10.0) },
+            ..Default::default()
+        }).unwrap();
+        for entity in 1..=3 {
+            let body = world.add_rigid_body(entity, &Body {
+                position: Vector3::new(entity as f32 * 2.0, 1.0, 0.0),
+                ..Default::default()
+            });
+            world.add_collider(entity, body, &ColliderDef::default()).unwrap();
+        }
+
+        world.step(DT * 3.0);
+        let stats = world.step_stats();
+        assert_eq!(stats.substeps, 3);
+        assert_eq!(stats.active_bodies, 3);
+        assert_eq!(stats.sleeping_bodies, 0);
+        assert_eq!(stats.contact_pairs, 3);
+        assert!(stats.step_time > Duration::ZERO);
+        assert!(stats.narrow_phase_time > Duration::ZERO && stats.solver_time > Duration::ZERO);
+        assert!(stats.step_time >= stats.broad_phase_time + stats.narrow_phase_time + stats.solver_time);
+
+        // not enough time accumulated for a substep
+        world.step(DT * 0.5);
+        assert_eq!(world.step_stats().substeps, 0);
+        assert_eq!(world.step_stats().solver_time, Duration::ZERO);
+
+        for _ in 0..300 {
+            world.step(DT);
+        }
+        assert_eq!(world.step_stats().sleeping_bodies, 3);
+        assert_eq!(world.step_stats().active_bodies, 0);
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rapier3d::prelude::{ActiveCollisionTypes, ColliderHandle as RapierColliderHandle};
+use serde::{Deserialize, Serialize};
+
+use crate::integration::rapier;
+use crate::{CollisionGroups, EntityId, PhysicsError, World};
+
+// Reports every entity by default
+#[derive(Clone, Debug, Default)]
+pub struct TriggerDef {
+    pub groups: CollisionGroups, // only entities whose colliders interact with these groups are reported
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TriggerEventType {
+    Enter, // first collider of the entity overlapped the trigger
+    Stay,  // once per step() while the entity stays inside
+    Exit,  // last collider of the entity left the trigger, or was removed
+}
+
+#[derive(Clone, Debug)]
+pub struct TriggerEvent {
+    pub trigger: EntityId,
+    pub entity: EntityId,
+    pub event_type: TriggerEventType,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct Trigger {
+    groups: CollisionGroups,
+    occupants: BTreeMap<EntityId, Occupant>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Occupant {
+    colliders: u32, // an entity is inside until the last of its colliders leaves
+    reported: bool, // false until the entity has been through a full step()
+}
+
+// Overlapping (trigger collider, other collider) pairs and the entities they were counted for
+pub(crate) type TriggerPairs = HashMap<(RapierColliderHandle, RapierColliderHandle), (EntityId, EntityId)>;
+
+impl World {
+    // Turns the sensor colliders on the entity's body into a trigger. They also start
+    // detecting kinematic bodies, so character controllers set triggers off.
+    pub fn add_trigger(&mut self, entity: EntityId, def: &TriggerDef) -> Result<(), PhysicsError> {
+        let sensors: Vec<_> = self.rigid_body(entity)?
+            .colliders()
+            .iter()
+            .copied()
+            .filter(|handle| self.collider_set[*handle].is_sensor())
+            .collect();
+
+        if sensors.is_empty() {
+            return Err(PhysicsError::NoSensorCollider(entity));
+        }
+
+        for handle in &sensors {
+            let collider = &mut self.collider_set[*handle];
+            collider.set_active_collision_types(
+                collider.active_collision_types() | ActiveCollisionTypes::KINEMATIC_FIXED | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
+            );
+        }
+
+        self.triggers.insert(entity, Trigger {
+            groups: def.groups,
+            occupants: BTreeMap::new(),
+        });
+
+        // rapier only reports overlaps when they start, bodies already inside enter now
+        let mut overlaps: Vec<_> = sensors
+            .iter()
+            .flat_map(|sensor| {
+                self.narrow_phase
+                    .intersection_pairs_with(*sensor)
+                    .filter(|(_, _, intersecting)| *intersecting)
+                    .map(move |(collider1, collider2, _)| (*sensor, if collider1 == *sensor { collider2 } else { collider1 }))
+            })
+            .collect();
+        overlaps.sort_by_key(|(_, other)| self.collider_entity(*other));
+        for (sensor, other) in overlaps {
+            self.trigger_entered(sensor, other);
+        }
+        Ok(())
+    }
+
+    // The sensors keep reporting plain collision events
+    pub fn remove_trigger(&mut self, entity: EntityId) -> bool {
+        self.trigger_pairs.retain(|_, (trigger, _)| *trigger != entity);
+        self.triggers.remove(&entity).is_some()
+    }
+
+    // Sorted by entity, empty when the entity is not a trigger
+    pub fn entities_in_trigger(&self, trigger: EntityId) -> Vec<EntityId> {
+        self.triggers
+            .get(&trigger)
+            .map(|trigger| trigger.occupants.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn is_in_trigger(&self, trigger: EntityId, entity: EntityId) -> bool {
+        self.triggers
+            .get(&trigger)
+            .is_some_and(|trigger| trigger.occupants.contains_key(&entity))
+    }
+
+    // Events accumulate until `clear_events` is called
+    pub fn trigger_events(&self) -> &[TriggerEvent] {
+        &self.trigger_events
+    }
+
+    // Called for every sensor event, in both directions since both colliders may be triggers
+    pub(crate) fn track_trigger_overlap(&mut self, collider1: RapierColliderHandle, collider2: RapierColliderHandle, started: bool) {
+        for (sensor, other) in [(collider1, collider2), (collider2, collider1)] {
+            if started {
+                self.trigger_entered(sensor, other);
+            } else if let Some((trigger, entity)) = self.trigger_pairs.remove(&(sensor, other)) {
+                self.trigger_exited(trigger, entity);
+            }
+        }
+    }
+
+    fn trigger_entered(&mut self, sensor: RapierColliderHandle, other: RapierColliderHandle) {
+        let (Some(trigger), Some(entity)) = (self.collider_entity(sensor), self.collider_entity(other)) else {
+            return;
+        };
+        if trigger == entity || !self.collider_set[sensor].is_sensor() {
+            return;
+        }
+        let groups = rapier::from_interaction_groups(self.collider_set[other].collision_groups());
+        let Some(state) = self.triggers.get_mut(&trigger) else {
+            return;
+        };
+        if !state.groups.interacts_with(&groups) {
+            return;
+        }
+
+        self.trigger_pairs.insert((sensor, other), (trigger, entity));
+        let occupant = state.occupants.entry(entity).or_insert(Occupant { colliders: 0, reported: false });
+        occupant.colliders += 1;
+        if occupant.colliders == 1 {
+            self.trigger_events.push(TriggerEvent { trigger, entity, event_type: TriggerEventType::Enter });
+        }
+    }
+
+    fn trigger_exited(&mut self, trigger: EntityId, entity: EntityId) {
+        let Some(state) = self.triggers.get_mut(&trigger) else {
+            return;
+        };
+        let Some(occupant) = state.occupants.get_mut(&entity) else {
+            return;
+        };
+
+        occupant.colliders -= 1;
+        if occupant.colliders == 0 {
+            state.occupants.remove(&entity);
+            self.trigger_events.push(TriggerEvent { trigger, entity, event_type: TriggerEventType::Exit });
+        }
+    }
+
+    // Called once per step() like emit_ongoing_events
+    pub(crate) fn emit_trigger_stays(&mut self) {
+        // sorted so the events come out in the same order every run
+        let mut triggers: Vec<_> = self.triggers.iter_mut().collect();
+        triggers.sort_unstable_by_key(|(trigger, _)| **trigger);
+
+        for (trigger, state) in triggers {
+            for (entity, occupant) in state.occupants.iter_mut() {
+                if !occupant.reported {
+                    // the Enter event already covers this step
+                    occupant.reported = true;
+                    continue;
+                }
+                self.trigger_events.push(TriggerEvent {
+                    trigger: *trigger,
+                    entity: *entity,
+                    event_type: TriggerEventType::Stay,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, ColliderShape, CollisionLayers};
+    use gamerplex_math::Vector3;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn zone_world() -> World {
+        let mut world = World::new(Vector3::zeros());
+        let zone = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, zone, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(2.0, 2.0, 2.0) },
+            is_sensor: true,
+            ..Default::default()
+        }).unwrap();
+        world
+    }
+
+    fn add_mover(world: &mut World, entity: EntityId, body_type: BodyType, groups: CollisionGroups) {
+        let body = world.add_rigid_body(entity, &Body {
+            body_type,
+            position: Vector3::new(-5.0, 0.0, 0.0),
+            ..Default::default()
+        });
+        world.add_collider(entity, body, &ColliderDef { collision_groups: groups, ..Default::default() }).unwrap();
+    }
+
+    fn events_of(world: &World, event_type: TriggerEventType) -> Vec<EntityId> {
+        world.trigger_events()
+            .iter()
+            .filter(|event| event.event_type == event_type)
+            .map(|event| event.entity)
+            .collect()
+    }
+
+    #[test]
+    fn enter_stay_exit_for_matching_groups() {
+        let mut world = zone_world();
+        let mut layers = CollisionLayers::new();
+        let (player, prop) = (layers.define("player").unwrap(), layers.define("prop").unwrap());
+        layers.define("zone").unwrap();
+        world.add_trigger(0, &TriggerDef { groups: layers.groups(&["zone"], &["player"]).unwrap() }).unwrap();
+        add_mover(&mut world, 1, BodyType::Kinematic, CollisionGroups::new(player, u32::MAX));
+        add_mover(&mut world, 2, BodyType::Kinematic, CollisionGroups::new(prop, u32::MAX));
+
+        let step_to = |world: &mut World, x: f32| {
+            for entity in [1, 2] {
+                world.rigid_body_mut(entity).unwrap().set_next_kinematic_translation(Vector3::new(x, 0.0, 0.0).into());
+            }
+            world.clear_events();
+            world.step(DT);
+        };
+
+        step_to(&mut world, 0.0);
+        assert_eq!(events_of(&world, TriggerEventType::Enter), vec![1]);
+        assert_eq!(world.entities_in_trigger(0), vec![1]);
+
+        step_to(&mut world, 0.5);
+        assert_eq!(events_of(&world, TriggerEventType::Stay), vec![1]);
+        assert!(world.is_in_trigger(0, 1));
+        assert!(!world.is_in_trigger(0, 2));
+
+        step_to(&mut world, 5.0);
+        assert_eq!(events_of(&world, TriggerEventType::Exit), vec![1]);
+        assert!(world.entities_in_trigger(0).is_empty());
+    }
+
+    #[test]
+    fn removed_entities_exit() {
+        let mut world = zone_world();
+        world.add_trigger(0, &TriggerDef::default()).unwrap();
+        add_mover(&mut world, 1, BodyType::Dynamic, CollisionGroups::all());
+        world.rigid_body_mut(1).unwrap().set_translation(Vector3::zeros().into(), true);
+
+        world.step(DT);
+        assert_eq!(world.entities_in_trigger(0), vec![1]);
+
+        world.remove_entity(1).unwrap();
+        world.clear_events();
+        world.step(DT);
+        assert_eq!(events_of(&world, TriggerEventType::Exit), vec![1]);
+        assert!(world.entities_in_trigger(0).is_empty());
+    }
+
+    #[test]
+    fn triggers_need_a_sensor() {
+        let mut world = World::new(Vector3::zeros());
+        add_mover(&mut world, 1, BodyType::Dynamic, CollisionGroups::all());
+        assert_eq!(world.add_trigger(1, &TriggerDef::default()), Err(PhysicsError::NoSensorCollider(1)));
+    }
+
+    #[test]
+    fn bodies_already_inside_enter_when_the_trigger_is_added() {
+        let mut world = zone_world();
+        add_mover(&mut world, 1, BodyType::Dynamic, CollisionGroups::all());
+        world.rigid_body_mut(1).unwrap().set_translation(Vector3::zeros().into(), true);
+        world.step(DT);
+        assert!(world.trigger_events().is_empty());
+
+        world.add_trigger(0, &TriggerDef::default()).unwrap();
+        assert_eq!(events_of(&world, TriggerEventType::Enter), vec![1]);
+        assert_eq!(world.entities_in_trigger(0), vec![1]);
+
+        world.clear_events();
+        world.step(DT);
+        world.clear_events();
+        world.step(DT);
+        assert_eq!(events_of(&world, TriggerEventType::Stay), vec![1]);
+
+        world.rigid_body_mut(1).unwrap().set_translation(Vector3::new(5.0, 0.0, 0.0).into(), true);
+        world.clear_events();
+        world.step(DT);
+        assert_eq!(events_of(&world, TriggerEventType::Exit), vec![1]);
+        assert!(world.entities_in_trigger(0).is_empty());
+    }
+
+    #[test]
+    fn stays_come_out_in_trigger_order() {
+        let mut world = World::new(Vector3::zeros());
+        for trigger in [7, 2, 9, 4, 1, 8, 3, 6, 5] {
+            let zone = world.add_rigid_body(trigger, &Body { body_type: BodyType::Static, ..Default::default() });
+            world.add_collider(trigger, zone, &ColliderDef {
+                shape: ColliderShape::Box { half_extents: Vector3::new(2.0, 2.0, 2.0) },
+                is_sensor: true,
+                ..Default::default()
+            }).unwrap();
+            world.add_trigger(trigger, &TriggerDef::default()).unwrap();
+        }
+        add_mover(&mut world, 20, BodyType::Dynamic, CollisionGroups::all());
+        world.rigid_body_mut(20).unwrap().set_translation(Vector3::zeros().into(), true);
+        world.step(DT);
+
+        world.clear_events();
+        world.step(DT);
+        let stays: Vec<_> = world.trigger_events().iter().map(|event| event.trigger).collect();
+        assert_eq!(stays, (1..=9).collect::<Vec<_>>());
+    }
+}
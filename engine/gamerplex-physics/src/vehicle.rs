@@ -0,0 +1,302 @@
+use gamerplex_math::Vector3;
+use rapier3d::prelude::{QueryFilter as RapierQueryFilter, RigidBodyHandle};
+use serde::{Deserialize, Serialize};
+
+use crate::integration::rapier;
+use crate::{BodyHandle, EntityId, PhysicsError, World};
+
+// Chassis space axes, 0 = x, 1 = y, 2 = z
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VehicleDef {
+    pub wheels: Vec<WheelDef>,
+    pub up_axis: usize,
+    pub forward_axis: usize,
+}
+
+impl Default for VehicleDef {
+    fn default() -> Self {
+        Self {
+            wheels: Vec::new(),
+            up_axis: 1,
+            forward_axis: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WheelDef {
+    pub connection_point: Vector3,    // chassis space, where the suspension is mounted
+    pub direction: Vector3,           // chassis space, the suspension pushes the wheel this way
+    pub axle: Vector3,                // chassis space, the wheel spins around this
+    pub radius: f32,
+    pub suspension_rest_length: f32,
+    pub max_suspension_travel: f32,   // how far the suspension may move from its rest length
+    pub suspension_stiffness: f32,
+    pub suspension_compression: f32,  // damping while the spring is being compressed
+    pub suspension_damping: f32,      // damping while it extends, raise it if the car bounces
+    pub max_suspension_force: f32,
+    pub friction_slip: f32,           // grip, higher values brake harder but may flip the car
+    pub side_friction_stiffness: f32,
+    pub driven: bool,                 // receives engine force from drive_vehicle
+    pub steered: bool,                // receives the steering angle from drive_vehicle
+}
+
+impl Default for WheelDef {
+    fn default() -> Self {
+        Self {
+            connection_point: Vector3::zeros(),
+            direction: Vector3::new(0.0, -1.0, 0.0),
+            axle: Vector3::new(0.0, 0.0, 1.0),
+            radius: 0.4,
+            suspension_rest_length: 0.3,
+            max_suspension_travel: 0.2,
+            suspension_stiffness: 30.0,
+            suspension_compression: 2.3,
+            suspension_damping: 4.4,
+            max_suspension_force: 6000.0,
+            friction_slip: 10.5,
+            side_friction_stiffness: 1.0,
+            driven: true,
+            steered: false,
+        }
+    }
+}
+
+// Inputs of a single wheel, kept until changed
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WheelControl {
+    pub engine_force: f32,
+    pub brake: f32,    // braking impulse, 0 lets the wheel roll freely
+    pub steering: f32, // radians around the suspension direction
+}
+
+// What a wheel did during the last substep
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WheelState {
+    pub in_contact: bool,
+    pub ground: Option<EntityId>,    // None when airborne or on a collider without body
+    pub contact_point: Vector3,      // world space
+    pub contact_normal: Vector3,
+    pub suspension_length: f32,
+    pub suspension_force: f32,
+    pub rotation: f32,               // radians rolled around the axle, for rendering
+    pub skidding: bool,              // the tire force hit its friction limit
+    pub slip_speed: f32,             // sideways speed of the contact point, e.g. for tire marks
+}
+
+impl Default for WheelState {
+    fn default() -> Self {
+        Self {
+            in_contact: false,
+            ground: None,
+            contact_point: Vector3::zeros(),
+            contact_normal: Vector3::zeros(),
+            suspension_length: 0.0,
+            suspension_force: 0.0,
+            rotation: 0.0,
+            skidding: false,
+            slip_speed: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct Vehicle {
+    def: VehicleDef,
+    controls: Vec<WheelControl>,
+    states: Vec<WheelState>,
+    spin: Vec<f32>, // rotation per substep, keeps airborne wheels turning
+    speed: f32,
+}
+
+impl World {
+    // The chassis must be a dynamic body, wheels are rays and need no colliders
+    pub fn add_vehicle(&mut self, chassis: BodyHandle, def: &VehicleDef) -> Result<(), PhysicsError> {
+        let entity = self.body_entity(chassis).ok_or(PhysicsError::InvalidBodyHandle(chassis))?;
+        if !self.rigid_body(entity)?.is_dynamic() {
+            return Err(PhysicsError::BodyNotDynamic(entity));
+        }
+
+        let wheel_count = def.wheels.len();
+        self.vehicles.insert(chassis.to_rapier_handle(), Vehicle {
+            def: def.clone(),
+            controls: vec![WheelControl::default(); wheel_count],
+            states: vec![WheelState::default(); wheel_count],
+            spin: vec![0.0; wheel_count],
+            speed: 0.0,
+        });
+        Ok(())
+    }
+
+    pub fn remove_vehicle(&mut self, chassis: BodyHandle) -> bool {
+        self.vehicles.remove(&chassis.to_rapier_handle()).is_some()
+    }
+
+    // Convenience for the usual car controls, engine force goes to driven wheels,
+    // steering to steered wheels and the brake to every wheel
+    pub fn drive_vehicle(&mut self, chassis: BodyHandle, engine_force: f32, brake: f32, steering: f32) -> Result<(), PhysicsError> {
+        let vehicle = self.vehicle_mut(chassis)?;
+        for (wheel, control) in vehicle.def.wheels.iter().zip(vehicle.controls.iter_mut()) {
+            control.engine_force = if wheel.driven { engine_force } else { 0.0 };
+            control.steering = if wheel.steered { steering } else { 0.0 };
+            control.brake = brake;
+        }
+        Ok(())
+    }
+
+    pub fn set_wheel_control(&mut self, chassis: BodyHandle, wheel: usize, control: WheelControl) -> Result<(), PhysicsError> {
+        let vehicle = self.vehicle_mut(chassis)?;
+        let slot = vehicle.controls.get_mut(wheel).ok_or(PhysicsError::InvalidWheel(wheel))?;
+        *slot = control;
+        Ok(())
+    }
+
+    pub fn wheel_states(&self, chassis: BodyHandle) -> Result<&[WheelState], PhysicsError> {
+        self.vehicles
+            .get(&chassis.to_rapier_handle())
+            .map(|vehicle| vehicle.states.as_slice())
+            .ok_or(PhysicsError::NoVehicle(chassis))
+    }
+
+    // Signed speed along the chassis forward axis
+    pub fn vehicle_speed(&self, chassis: BodyHandle) -> Result<f32, PhysicsError> {
+        self.vehicles
+            .get(&chassis.to_rapier_handle())
+            .map(|vehicle| vehicle.speed)
+            .ok_or(PhysicsError::NoVehicle(chassis))
+    }
+
+    fn vehicle_mut(&mut self, chassis: BodyHandle) -> Result<&mut Vehicle, PhysicsError> {
+        self.vehicles
+            .get_mut(&chassis.to_rapier_handle())
+            .ok_or(PhysicsError::NoVehicle(chassis))
+    }
+
+    // Runs before every substep. The rapier controller is rebuilt from the def each
+    // time so vehicles stay plain data and survive snapshots.
+    pub(crate) fn update_vehicles(&mut self, dt: f32) {
+        let mut chassis_handles: Vec<RigidBodyHandle> = self.vehicles.keys().copied().collect();
+        // vehicles push on each other through the bodies, keep the order stable
+        chassis_handles.sort_by_key(|handle| handle.into_raw_parts());
+
+        for handle in chassis_handles {
+            if !self.rigid_body_set.get(handle).is_some_and(|body| body.is_enabled()) {
+                continue;
+            }
+            let vehicle = &self.vehicles[&handle];
+            let mut controller = rapier::create_vehicle_controller(handle, &vehicle.def);
+            for ((wheel, control), state) in controller.wheels_mut().iter_mut().zip(&vehicle.controls).zip(&vehicle.states) {
+                wheel.engine_force = control.engine_force;
+                wheel.brake = control.brake;
+                wheel.steering = control.steering;
+                wheel.rotation = state.rotation;
+            }
+
+            let filter = RapierQueryFilter::new().exclude_rigid_body(handle).exclude_sensors();
+            controller.update_vehicle(dt, &mut self.rigid_body_set, &self.collider_set, &self.query_pipeline, filter);
+
+            let chassis = &self.rigid_body_set[handle];
+            let mut states = Vec::with_capacity(controller.wheels().len());
+            let vehicle = &self.vehicles[&handle];
+            for (wheel, (state, spin)) in controller.wheels().iter().zip(vehicle.states.iter().zip(&vehicle.spin)) {
+                let contact = wheel.raycast_info();
+                let ground = contact.ground_object
+                    .filter(|_| contact.is_in_contact)
+                    .and_then(|collider| self.collider_entity(collider));
+
+                // same limit the controller scales the tire impulses down to
+                let max_impulse = wheel.wheel_suspension_force * dt * wheel.friction_slip;
+                let impulse = (wheel.forward_impulse * 0.5).hypot(wheel.side_impulse);
+                let axle = wheel.axle();
+                let velocity = chassis.velocity_at_point(&contact.contact_point_ws);
+
+                let rotation = if contact.is_in_contact { wheel.rotation } else { state.rotation + spin };
+                states.push(WheelState {
+                    in_contact: contact.is_in_contact,
+                    ground,
+                    contact_point: Vector3::from(contact.contact_point_ws),
+                    contact_normal: Vector3::from(contact.contact_normal_ws),
+                    suspension_length: contact.suspension_length,
+                    suspension_force: wheel.wheel_suspension_force.min(wheel.max_suspension_force),
+                    rotation,
+                    skidding: contact.is_in_contact && max_impulse > 0.0 && impulse >= max_impulse * 0.999,
+                    slip_speed: if contact.is_in_contact { velocity.dot(&axle).abs() } else { 0.0 },
+                });
+            }
+
+            let vehicle = self.vehicles.get_mut(&handle).unwrap();
+            for (spin, (old, new)) in vehicle.spin.iter_mut().zip(vehicle.states.iter().zip(&states)) {
+                *spin = if new.in_contact { new.rotation - old.rotation } else { *spin * 0.99 };
+            }
+            vehicle.states = states;
+            vehicle.speed = controller.current_vehicle_speed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyType, ColliderDef, ColliderShape};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn car_world() -> (World, BodyHandle) {
+        let mut world = World::new(Vector3::new(0.0, -9.81, 0.0));
+
+        let ground = world.add_rigid_body(0, &Body { body_type: BodyType::Static, ..Default::default() });
+        world.add_collider(0, ground, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(200.0, 0.5, 200.0) },
+            ..Default::default()
+        }).unwrap();
+
+        let chassis = world.add_rigid_body(1, &Body { position: Vector3::new(0.0, 1.5, 0.0), ..Default::default() });
+        world.add_collider(1, chassis, &ColliderDef {
+            shape: ColliderShape::Box { half_extents: Vector3::new(2.0, 0.3, 1.0) },
+            density: 100.0,
+            ..Default::default()
+        }).unwrap();
+
+        let wheels = [(1.5, 0.9), (1.5, -0.9), (-1.5, 0.9), (-1.5, -0.9)]
+            .into_iter()
+            .map(|(x, z)| WheelDef {
+                connection_point: Vector3::new(x, -0.2, z),
+                steered: x > 0.0,
+                ..Default::default()
+            })
+            .collect();
+        world.add_vehicle(chassis, &VehicleDef { wheels, ..Default::default() }).unwrap();
+        (world, chassis)
+    }
+
+    #[test]
+    fn vehicle_settles_and_drives_forward() {
+        let (mut world, chassis) = car_world();
+        for _ in 0..120 {
+            world.step(DT);
+        }
+
+        let states = world.wheel_states(chassis).unwrap();
+        assert!(states.iter().all(|wheel| wheel.in_contact && wheel.ground == Some(0)));
+        assert!(states.iter().all(|wheel| wheel.suspension_force > 0.0));
+
+        world.drive_vehicle(chassis, 2000.0, 0.0, 0.0).unwrap();
+        for _ in 0..120 {
+            world.step(DT);
+        }
+
+        assert!(world.vehicle_speed(chassis).unwrap() > 1.0);
+        assert!(world.rigid_body(1).unwrap().translation().x > 1.0);
+        assert!(world.wheel_states(chassis).unwrap()[0].rotation > 0.0);
+    }
+
+    #[test]
+    fn vehicles_need_a_dynamic_chassis() {
+        let (mut world, chassis) = car_world();
+        let ground = world.body_handle(0).unwrap();
+
+        assert_eq!(world.add_vehicle(ground, &VehicleDef::default()), Err(PhysicsError::BodyNotDynamic(0)));
+        assert_eq!(world.set_wheel_control(chassis, 9, WheelControl::default()), Err(PhysicsError::InvalidWheel(9)));
+        assert_eq!(world.wheel_states(ground).unwrap_err(), PhysicsError::NoVehicle(ground));
+    }
+}
@@ -1,10 +1,19 @@
-use std::collections::HashMap;
-use std::fmt::Error;
+use std::collections::{HashMap, HashSet};
 
 use crate::body::*;
 use crate::collider::*;
+use crate::joint::*;
+use crate::character::CharacterController;
 use crate::events::*;
+use crate::filter::{HookBridge, PhysicsHooks};
+use crate::material::{self, PhysicsMaterial};
+use crate::trigger::{Trigger, TriggerEvent, TriggerPairs};
+use crate::vehicle::Vehicle;
+use crate::forces::ForceField;
+use crate::stats::StepStats;
+use crate::sleep::SleepEvent;
 use crate::handles::*;
+use crate::error::PhysicsError;
 
 use gamerplex_math::{Vector3, Quaternion};
 use crate::integration::rapier;
@@ -14,7 +23,6 @@ use rapier3d::prelude::{
    IntegrationParameters,
    PhysicsPipeline,
    IslandManager,
-   BroadPhase,
    NarrowPhase,
    ImpulseJointSet,
    MultibodyJointSet,
@@ -22,38 +30,83 @@ use rapier3d::prelude::{
    QueryPipeline,
    ChannelEventCollector,
    DefaultBroadPhase,
-   RigidBodyHandle
+   RigidBodyHandle,
+   RigidBody,
+   Isometry,
+   Vector,
+   ImpulseJointHandle,
+   ContactForceEvent as RapierContactForceEvent,
+   CollisionEvent as RapierCollisionEvent,
 };
+use crossbeam::channel::Receiver;
 
 // the physical world struct 
 pub struct  World {
    // Rapier physics objects
-   rigid_body_set: RigidBodySet,
-   collider_set: ColliderSet,
-   integration_parameters: IntegrationParameters,
-   physics_pipeline: PhysicsPipeline,
-   island_manager: IslandManager,
-   broad_phase: DefaultBroadPhase,
-   narrow_phase: NarrowPhase,
-   impulse_joint_set: ImpulseJointSet,
-   multibody_joint_set: MultibodyJointSet,
-   ccd_solver: CCDSolver,
-   query_pipeline: QueryPipeline,
-
-   gravity: Vector3,
-   simulation_rate: f32,
+   pub(crate) rigid_body_set: RigidBodySet,
+   pub(crate) collider_set: ColliderSet,
+   pub(crate) integration_parameters: IntegrationParameters,
+   pub(crate) physics_pipeline: PhysicsPipeline,
+   pub(crate) island_manager: IslandManager,
+   pub(crate) broad_phase: DefaultBroadPhase,
+   pub(crate) narrow_phase: NarrowPhase,
+   pub(crate) impulse_joint_set: ImpulseJointSet,
+   pub(crate) multibody_joint_set: MultibodyJointSet,
+   pub(crate) ccd_solver: CCDSolver,
+   pub(crate) query_pipeline: QueryPipeline,
+
+   pub(crate) gravity: Vector3,
+   pub(crate) simulation_rate: f32,
 
    // tracking
    pub entity_body_map: HashMap<EntityId, BodyHandle>, // create BodyHandle and EntityId in handle.rs
-   body_entity_map: HashMap<RigidBodyHandle, EntityId>,
-   entity_collider_map: HashMap<EntityId, Vec<ColliderHandle>>,
+   pub(crate) body_entity_map: HashMap<RigidBodyHandle, EntityId>,
+   pub(crate) entity_collider_map: HashMap<EntityId, Vec<ColliderHandle>>,
+   // the entity each collider is registered under in entity_collider_map
+   pub(crate) collider_entity_map: HashMap<rapier3d::prelude::ColliderHandle, EntityId>,
+   // the shape each collider was created from, for exporting scenes
+   pub(crate) collider_shapes: HashMap<rapier3d::prelude::ColliderHandle, ColliderShape>,
 
    //collision event collection
    pub collision_events: Vec<CollisionEvent>,
-   event_handler: ChannelEventCollector,
+   pub(crate) contact_force_events: Vec<ContactForceEvent>,
+   pub(crate) event_handler: ChannelEventCollector,
+   pub(crate) collision_recv: Receiver<RapierCollisionEvent>,
+   pub(crate) contact_force_recv: Receiver<RapierContactForceEvent>,
+   pub(crate) active_contacts: HashMap<ContactKey, ActiveContact>,
+
+   // joints created through add_joint
+   pub(crate) joints: HashMap<ImpulseJointHandle, JointInfo>,
+   pub(crate) joint_broken_events: Vec<JointBrokenEvent>,
+
+   pub(crate) character_controllers: HashMap<EntityId, CharacterController>,
+   pub(crate) vehicles: HashMap<RigidBodyHandle, Vehicle>,
+   pub(crate) force_fields: HashMap<EntityId, ForceField>,
+   pub(crate) triggers: HashMap<EntityId, Trigger>,
+   pub(crate) trigger_pairs: TriggerPairs,
+   pub(crate) trigger_events: Vec<TriggerEvent>,
+   // bodies seen asleep after the last substep
+   pub(crate) sleeping_bodies: HashSet<RigidBodyHandle>,
+   pub(crate) sleep_events: Vec<SleepEvent>,
+   // bodies whose mass is not simply computed from their colliders
+   pub(crate) mass_modes: HashMap<RigidBodyHandle, MassMode>,
+   // densities of the colliders zeroed while their body has a custom mass
+   pub(crate) custom_mass_densities: HashMap<rapier3d::prelude::ColliderHandle, f32>,
+   pub(crate) physics_hooks: Option<Box<dyn PhysicsHooks>>,
+   // indexed by MaterialId
+   pub(crate) materials: Vec<(String, PhysicsMaterial)>,
    
    //time tracking
-   accumulated_time: f32,
+   pub(crate) accumulated_time: f32,
+   pub(crate) max_substeps: u32,
+   // pose of every body before the last substep, for interpolation
+   pub(crate) previous_poses: HashMap<RigidBodyHandle, Isometry<f32>>,
+   pub(crate) step_stats: StepStats,
+   pub(crate) ccd_active_bodies: Vec<EntityId>,
+
+   // None runs on rayon's global pool
+   #[cfg(feature = "parallel")]
+   pub(crate) thread_pool: Option<std::sync::Arc<rayon::ThreadPool>>,
 }
 
 impl World {
@@ -69,7 +122,7 @@ impl World {
          rigid_body_set: RigidBodySet::new(),
          collider_set: ColliderSet::new(),
          integration_parameters: IntegrationParameters::default(),
-         physics_pipeline: PhysicsPipeline::new(),
+         physics_pipeline: rapier::create_pipeline(),
          island_manager: IslandManager::new(),
          broad_phase: DefaultBroadPhase::new(),
          narrow_phase: NarrowPhase::new(),
@@ -78,36 +131,98 @@ impl World {
          ccd_solver: CCDSolver::new(),
          query_pipeline: QueryPipeline::new(),
 
-         gravity: gravity,
+         gravity,
          simulation_rate: 1.0 / 60.0, // 60 Hz 
          
          collision_events: Vec::new(),
+         contact_force_events: Vec::new(),
          event_handler,
+         collision_recv,
+         contact_force_recv,
+         active_contacts: HashMap::new(),
+
+         joints: HashMap::new(),
+         joint_broken_events: Vec::new(),
+
+         character_controllers: HashMap::new(),
+         vehicles: HashMap::new(),
+         force_fields: HashMap::new(),
+         triggers: HashMap::new(),
+         trigger_pairs: TriggerPairs::new(),
+         trigger_events: Vec::new(),
+         sleeping_bodies: HashSet::new(),
+         sleep_events: Vec::new(),
+         mass_modes: HashMap::new(),
+         custom_mass_densities: HashMap::new(),
+         physics_hooks: None,
+         materials: material::default_materials(),
          
          entity_body_map: HashMap::new(),
          body_entity_map: HashMap::new(),
          entity_collider_map: HashMap::new(),
+         collider_entity_map: HashMap::new(),
+         collider_shapes: HashMap::new(),
          
          accumulated_time: 0.0,
+         max_substeps: 8,
+         previous_poses: HashMap::new(),
+         step_stats: StepStats::default(),
+         ccd_active_bodies: Vec::new(),
+
+         #[cfg(feature = "parallel")]
+         thread_pool: None,
      }
    }
 
    pub fn step(&mut self, delta_time: f32) {
       // Fixed timestep physics update
       self.accumulated_time += delta_time;
-      
-      while self.accumulated_time >= self.simulation_rate {
+      let mut substeps = 0;
+      let started = std::time::Instant::now();
+      self.step_stats = StepStats::default();
+      self.ccd_active_bodies.clear();
+
+      while self.accumulated_time >= self.simulation_rate && substeps < self.max_substeps {
           self.step_simulation(self.simulation_rate);
           self.accumulated_time -= self.simulation_rate;
+          substeps += 1;
       }
+
+      // after a long hitch drop the steps we could not catch up on, otherwise
+      // every following frame falls further behind
+      if self.accumulated_time >= self.simulation_rate {
+          self.accumulated_time %= self.simulation_rate;
+      }
+
+      if substeps > 0 {
+          self.emit_ongoing_events();
+          self.emit_trigger_stays();
+      }
+
+      self.count_bodies_and_contacts();
+      self.step_stats.step_time = started.elapsed();
   }
 
   fn step_simulation(&mut self, dt: f32) {
    // opdate gravity
-   let gravity = rapier::to_na_vector(&self.gravity);
+   let gravity = Vector::from(self.gravity);
+   self.integration_parameters.dt = dt;
+
+   self.previous_poses.clear();
+   for (handle, body) in self.rigid_body_set.iter() {
+      self.previous_poses.insert(handle, *body.position());
+   }
+
+   self.apply_force_fields(dt);
+   self.update_vehicles(dt);
+
+   let hooks = HookBridge {
+      hooks: self.physics_hooks.as_deref(),
+      body_entity_map: &self.body_entity_map,
+   };
 
    // run simulation
-   self.physics_pipeline.step(
+   let mut run_pipeline = || self.physics_pipeline.step(
       &gravity,
       &self.integration_parameters,
       &mut self.island_manager,
@@ -118,13 +233,26 @@ impl World {
       &mut self.impulse_joint_set,
       &mut self.multibody_joint_set,
       &mut self.ccd_solver,
-      None,
-      &(),
+      Some(&mut self.query_pipeline),
+      &hooks,
       &self.event_handler,
    );
 
-   // Process collision events
-   // (implementation details depend on your event system)
+   #[cfg(feature = "parallel")]
+   match &self.thread_pool {
+      Some(pool) => pool.install(run_pipeline),
+      None => run_pipeline(),
+   }
+   #[cfg(not(feature = "parallel"))]
+   run_pipeline();
+   self.step_stats.add_substep(&self.physics_pipeline);
+   self.collect_ccd_active_bodies();
+
+   // drain the rapier channels after every substep so the narrow phase
+   // still holds the contacts the events refer to
+   self.process_collision_events();
+   self.break_overloaded_joints(dt);
+   self.track_sleep_changes();
   }
 
   pub fn synchronize_transforms(&self) -> Vec<(EntityId, Vector3, Quaternion)> {
@@ -132,8 +260,8 @@ impl World {
    
    for (body_handle, entity) in &self.body_entity_map {
        if let Some(body) = self.rigid_body_set.get(*body_handle) {
-           let position = rapier::from_na_vector(&body.translation());
-           let rotation = rapier::from_na_quaternion(&body.rotation());
+           let position = Vector3::from(body.translation());
+           let rotation = Quaternion::from(body.rotation());
            
            transforms.push((*entity, position, rotation));
        }
@@ -142,33 +270,182 @@ impl World {
    transforms
   }
 
+  // Blends between the pose before and after the last step by interpolation_alpha,
+  // so rendering faster than the step rate does not stutter. Lags one step behind.
+  pub fn synchronize_interpolated_transforms(&self) -> Vec<(EntityId, Vector3, Quaternion)> {
+   let alpha = self.interpolation_alpha();
+   let mut transforms = Vec::new();
+
+   for (body_handle, entity) in &self.body_entity_map {
+       if let Some(body) = self.rigid_body_set.get(*body_handle) {
+           let current = body.position();
+           // bodies added since the last step have nothing to blend from
+           let previous = self.previous_poses.get(body_handle).unwrap_or(current);
+
+           let position = previous.translation.vector.lerp(&current.translation.vector, alpha);
+           let rotation = previous.rotation.slerp(&current.rotation, alpha);
+
+           transforms.push((*entity, Vector3::from(position), Quaternion::from(rotation)));
+       }
+   }
+
+   transforms
+  }
+
+  // How far the accumulator is into the next step, from 0 to 1
+  pub fn interpolation_alpha(&self) -> f32 {
+   (self.accumulated_time / self.simulation_rate).clamp(0.0, 1.0)
+  }
+
+  // Steps per second of the fixed timestep, 60 by default. Rates that are not
+  // finite and positive are ignored, the world would never step.
+  pub fn set_step_rate(&mut self, steps_per_second: f32) {
+   if steps_per_second.is_finite() && steps_per_second > 0.0 {
+      self.simulation_rate = 1.0 / steps_per_second;
+   }
+  }
+
+  pub fn step_rate(&self) -> f32 {
+   1.0 / self.simulation_rate
+  }
+
+  // Most steps a single call to `step` may run, 8 by default
+  pub fn set_max_substeps(&mut self, max_substeps: u32) {
+   self.max_substeps = max_substeps.max(1);
+  }
+
   pub fn add_rigid_body(&mut self, entity: EntityId, def: &Body) -> BodyHandle {
    let body = rapier::create_rigid_body(def);
-   let handle_body_entity_map = self.rigid_body_set.insert(body);
-   let handle = BodyHandle::new(entity);
+   let rapier_handle = self.rigid_body_set.insert(body);
+   let handle = BodyHandle::from_rapier_handle(rapier_handle);
    
    self.entity_body_map.insert(entity, handle);
-   self.body_entity_map.insert(handle_body_entity_map, entity);
+   self.body_entity_map.insert(rapier_handle, entity);
+
+   if def.body_type == BodyType::Dynamic && def.mass != MassMode::Computed {
+      self.mass_modes.insert(rapier_handle, def.mass.clone());
+      self.refresh_mass_properties(rapier_handle);
+   }
    
-   BodyHandle(handle.into_raw_parts().0)
+   handle
   }
 
-  pub fn add_collider(&mut self, entity: EntityId, body_handle: BodyHandle, def: &ColliderDef) -> ColliderHandle {
-   let rapier_body_handle = BodyHandle::from_raw_parts(body_handle.0, 0).to_rapier_handle();
-   let collider = rapier::create_collider(def);
+  pub fn add_collider(&mut self, entity: EntityId, body_handle: BodyHandle, def: &ColliderDef) -> Result<ColliderHandle, PhysicsError> {
+   let rapier_body_handle = body_handle.to_rapier_handle();
+   // a stale handle must not attach the collider to whichever body reused the slot
+   if !self.rigid_body_set.contains(rapier_body_handle) {
+      return Err(PhysicsError::InvalidBodyHandle(body_handle));
+   }
+
+   let material = self.material(def.material)?;
+   let mut collider = rapier::create_collider(def)?.build();
+   material::apply_material(&mut collider, def.material, material);
    
    let handle = self.collider_set.insert_with_parent(
        collider,
        rapier_body_handle,
        &mut self.rigid_body_set
    );
+   self.collider_shapes.insert(handle, def.shape.clone());
+   self.collider_entity_map.insert(handle, entity);
+   let handle = ColliderHandle::from_rapier_handle(handle);
+   self.refresh_mass_properties(rapier_body_handle);
    
    self.entity_collider_map
        .entry(entity)
-       .or_insert_with(Vec::new)
-       .push(ColliderHandle(handle.into_raw_parts().0));
+       .or_default()
+       .push(handle);
    
-   ColliderHandle(handle.into_raw_parts().0)
+   Ok(handle)
    }
 
-}
\ No newline at end of file
+  pub fn body_handle(&self, entity: EntityId) -> Option<BodyHandle> {
+   self.entity_body_map.get(&entity).copied()
+  }
+
+  // None for stale handles whose body has been removed
+  pub fn body_entity(&self, handle: BodyHandle) -> Option<EntityId> {
+   self.body_entity_map.get(&handle.to_rapier_handle()).copied()
+  }
+
+  pub fn colliders(&self, entity: EntityId) -> &[ColliderHandle] {
+   self.entity_collider_map.get(&entity).map(Vec::as_slice).unwrap_or(&[])
+  }
+
+  pub(crate) fn rigid_body(&self, entity: EntityId) -> Result<&RigidBody, PhysicsError> {
+   let handle = self.entity_body_map.get(&entity).ok_or(PhysicsError::NoRigidBody(entity))?;
+   self.rigid_body_set
+      .get(handle.to_rapier_handle())
+      .ok_or(PhysicsError::NoRigidBody(entity))
+  }
+
+  pub(crate) fn rigid_body_mut(&mut self, entity: EntityId) -> Result<&mut RigidBody, PhysicsError> {
+   let handle = self.entity_body_map.get(&entity).ok_or(PhysicsError::NoRigidBody(entity))?;
+   self.rigid_body_set
+      .get_mut(handle.to_rapier_handle())
+      .ok_or(PhysicsError::NoRigidBody(entity))
+  }
+
+  // forces, impulses and velocities are only meaningful on dynamic bodies
+  pub(crate) fn dynamic_body_mut(&mut self, entity: EntityId) -> Result<&mut RigidBody, PhysicsError> {
+   let body = self.rigid_body_mut(entity)?;
+   if !body.is_dynamic() {
+      return Err(PhysicsError::BodyNotDynamic(entity));
+   }
+   Ok(body)
+  }
+
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   const DT: f32 = 1.0 / 60.0;
+
+   #[test]
+   fn invalid_step_rates_are_ignored() {
+      let mut world = World::new(Vector3::zeros());
+      for rate in [0.0, -30.0, f32::NAN, f32::INFINITY] {
+         world.set_step_rate(rate);
+         assert!((world.step_rate() - 60.0).abs() < 1e-3);
+      }
+
+      world.set_step_rate(30.0);
+      world.step(1.0 / 30.0 + 1e-4);
+      assert_eq!(world.step_stats().substeps, 1);
+   }
+
+   #[test]
+   fn hitches_are_capped_and_the_rest_dropped() {
+      let mut world = World::new(Vector3::zeros());
+      world.set_max_substeps(4);
+
+      world.step(1.0 + 0.5 * DT);
+      assert_eq!(world.step_stats().substeps, 4);
+      // the steps beyond the cap are gone, only the partial one is kept
+      assert!((world.interpolation_alpha() - 0.5).abs() < 0.01, "{}", world.interpolation_alpha());
+
+      world.step(0.6 * DT);
+      assert_eq!(world.step_stats().substeps, 1);
+      assert!(world.interpolation_alpha() < 0.2);
+   }
+
+   #[test]
+   fn transforms_blend_between_steps() {
+      let mut world = World::new(Vector3::zeros());
+      world.add_rigid_body(1, &Body { linear_velocity: Vector3::new(60.0, 0.0, 0.0), ..Default::default() });
+
+      // nothing to blend from before the first step
+      assert_eq!(world.synchronize_interpolated_transforms()[0].1, Vector3::zeros());
+
+      world.step(DT);
+      world.step(0.5 * DT);
+      assert_eq!(world.step_stats().substeps, 0);
+
+      let (_, current, _) = world.synchronize_transforms()[0];
+      let (_, blended, _) = world.synchronize_interpolated_transforms()[0];
+      assert!((current.x - 1.0).abs() < 1e-3);
+      assert!((blended.x - 0.5).abs() < 0.01, "{:?}", blended);
+   }
+}